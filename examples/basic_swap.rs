@@ -3,7 +3,10 @@
  *
  * このサンプルは、SUIからCETUSへの交換ルートを検索する方法を示しています。
  */
-use cetus_aggregator_rust::{AggregatorClient, AggregatorClientTrait, FindRouterParams};
+use cetus_aggregator_rust::{
+    AggregatorClient, AggregatorClientTrait, FindRouterParams, Provider, ProviderFilter,
+    SplitAlgorithm,
+};
 use primitive_types::U256;
 
 #[tokio::main]
@@ -15,19 +18,19 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let client = AggregatorClient::new(None);
     println!(
         "クライアントを初期化しました: エンドポイント = {}",
-        client.endpoint
+        client.endpoint()
     );
 
     // パラメータを準備
     let params = FindRouterParams {
-        from: "0x2::sui::SUI".to_string(),
+        from: "0x2::sui::SUI".parse()?,
         target: "0x06864a6f921804860930db6ddbe2e16acdf8504495ea7481637a1c8b9a8fe54b::cetus::CETUS"
-            .to_string(),
+            .parse()?,
         amount: U256::from(1000000000u64), // 1 SUI
         by_amount_in: true,
-        depth: Some(3),       // 最大スワップ回数(3回まで)
-        split_count: Some(1), // 最大分割数(3ルートまで)
-        providers: Some(vec!["CETUS".to_string()]),
+        depth: Some(3),                                 // 最大スワップ回数(3回まで)
+        split: Some(SplitAlgorithm::Auto { count: 1 }), // 最大分割数
+        providers: ProviderFilter::Only(vec![Provider::Cetus]),
         ..Default::default()
     };
 
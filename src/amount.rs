@@ -0,0 +1,102 @@
+/**
+ * 金額の表現と演算
+ *
+ * このモジュールはAPIで扱う金額（`U256`）のシリアライズ/デシリアライズと、
+ * オーバーフローを検出するチェック付き演算のヘルパーを定義します。
+ */
+use primitive_types::{U256, U512};
+use serde::{Deserialize, Deserializer, Serializer};
+use serde_json::Value;
+
+use crate::error::AggregatorError;
+use crate::locale::tr;
+
+/// 10進数の文字列を`U256`に変換
+///
+/// 符号・小数点・指数表記を含む文字列、および`U256`に収まらない値は`None`を返します。
+pub fn parse_u256(s: &str) -> Option<U256> {
+    let s = s.trim();
+    if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    U256::from_dec_str(s).ok()
+}
+
+/// `U256`を10進数の文字列としてシリアライズするためのヘルパー関数
+pub fn serialize_u256<S>(value: &U256, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_str(&value.to_string())
+}
+
+/// JSONの数値と10進数の文字列のどちらからでも`U256`をデシリアライズするためのヘルパー関数
+///
+/// `u64`を超える数値も精度を失わずに読み取ります。
+pub fn deserialize_u256<'de, D>(deserializer: D) -> Result<U256, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = Value::deserialize(deserializer)?;
+    let parsed = match &value {
+        Value::String(s) => parse_u256(s),
+        Value::Number(n) => parse_u256(&n.to_string()),
+        _ => None,
+    };
+
+    parsed.ok_or_else(|| {
+        serde::de::Error::custom(tr!(
+            "金額として解釈できない値です: {}",
+            "value cannot be interpreted as an amount: {}",
+            value
+        ))
+    })
+}
+
+/// 金額の合計をオーバーフローを検出しながら計算
+///
+/// オーバーフローした場合は`None`を返します。
+pub fn checked_sum<I>(amounts: I) -> Option<U256>
+where
+    I: IntoIterator<Item = U256>,
+{
+    amounts
+        .into_iter()
+        .try_fold(U256::zero(), |sum, amount| sum.checked_add(amount))
+}
+
+/// `value * numerator / denominator`を切り捨てで計算
+///
+/// 中間結果は`U512`で計算するため途中でオーバーフローしません。
+/// `denominator`が0の場合、または結果が`U256`に収まらない場合は`None`を返します。
+pub fn mul_div(value: U256, numerator: U256, denominator: U256) -> Option<U256> {
+    if denominator.is_zero() {
+        return None;
+    }
+    let result = U512::from(value) * U512::from(numerator) / U512::from(denominator);
+    U256::try_from(result).ok()
+}
+
+/// `value * numerator / denominator`を切り上げで計算
+///
+/// `denominator`が0の場合、または結果が`U256`に収まらない場合は`None`を返します。
+pub fn mul_div_ceil(value: U256, numerator: U256, denominator: U256) -> Option<U256> {
+    if denominator.is_zero() {
+        return None;
+    }
+    let product = U512::from(value) * U512::from(numerator);
+    let denominator = U512::from(denominator);
+    let mut result = product / denominator;
+    if !(product % denominator).is_zero() {
+        result += U512::one();
+    }
+    U256::try_from(result).ok()
+}
+
+/// レスポンスの金額を使った計算がオーバーフローした場合のエラー
+pub(crate) fn amount_overflow() -> AggregatorError {
+    AggregatorError::InvalidResponse(tr!(
+        "金額の計算がオーバーフローしました",
+        "amount calculation overflowed"
+    ))
+}
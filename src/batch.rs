@@ -0,0 +1,105 @@
+/**
+ * 複数のルート検索の一括実行
+ *
+ * このモジュールは多数のペア・金額のルート検索をクライアントの制限の範囲内で並行して実行する機能を定義します。
+ * 結果は入力と同じ順序で、項目ごとの結果と所要時間を返します。
+ */
+use std::time::{Duration, Instant};
+
+use tokio::task::JoinSet;
+
+use crate::client::AggregatorClient;
+use crate::error::Result;
+use crate::limit::Priority;
+use crate::models::{FindRouterParams, RouterData};
+
+/// 同時実行数の制限（[`max_in_flight`](crate::client::AggregatorClientBuilder::max_in_flight)）が
+/// 設定されていない場合に、一括実行で同時に実行する項目数の上限
+pub const DEFAULT_BATCH_CONCURRENCY: usize = 8;
+
+/// 一括実行の1項目の結果
+#[derive(Debug)]
+pub struct BatchItem {
+    /// ルート検索のパラメータ
+    pub params: FindRouterParams,
+    /// ルート検索の結果
+    pub result: Result<Option<RouterData>>,
+    /// 一括実行の開始から、この項目の実行を開始するまでの時間
+    pub started_after: Duration,
+    /// この項目の所要時間（制限の待機と再試行を含む）
+    pub elapsed: Duration,
+}
+
+impl AggregatorClient {
+    /// 複数のルート検索を並行して実行
+    ///
+    /// 各項目はクライアントのレート制限・同時実行数の制限・リトライポリシーに従って実行され、
+    /// 結果は`params`と同じ順序で返します。1つの項目が失敗しても他の項目は実行されます。
+    ///
+    /// 同時に実行する項目数は[`max_in_flight`](crate::client::AggregatorClientBuilder::max_in_flight)、
+    /// 設定されていない場合は[`DEFAULT_BATCH_CONCURRENCY`]までです。
+    /// 返されたFutureをドロップすると、実行中・待機中の項目はすべてキャンセルされます。
+    pub async fn find_routers_batch(&self, params: Vec<FindRouterParams>) -> Vec<BatchItem> {
+        self.find_routers_batch_with_priority(params, Priority::Normal)
+            .await
+    }
+
+    /// 優先度を指定して複数のルート検索を並行して実行
+    ///
+    /// バックグラウンドのスキャンには[`Priority::Low`]を指定すると、
+    /// 同じクライアントで送信するレイテンシが重要なリクエストを優先できます。
+    pub async fn find_routers_batch_with_priority(
+        &self,
+        params: Vec<FindRouterParams>,
+        priority: Priority,
+    ) -> Vec<BatchItem> {
+        let batch_started = Instant::now();
+        let count = params.len();
+        let concurrency = self
+            .max_in_flight()
+            .unwrap_or(DEFAULT_BATCH_CONCURRENCY)
+            .max(1);
+
+        // JoinSetはドロップ時にすべてのタスクを中断するため、キャンセルのガードとして機能する
+        let mut tasks = JoinSet::new();
+        let spawn = |tasks: &mut JoinSet<(usize, BatchItem)>,
+                     (index, params): (usize, FindRouterParams)| {
+            let client = self.clone();
+            tasks.spawn(async move {
+                let started = Instant::now();
+                let result = client
+                    .find_routers_with_priority(params.clone(), priority)
+                    .await;
+                let item = BatchItem {
+                    params,
+                    result,
+                    started_after: started.duration_since(batch_started),
+                    elapsed: started.elapsed(),
+                };
+                (index, item)
+            });
+        };
+
+        // 上限までの項目を開始し、1つ完了するごとに次の項目を開始する
+        let mut pending = params.into_iter().enumerate();
+        for next in pending.by_ref().take(concurrency) {
+            spawn(&mut tasks, next);
+        }
+
+        let mut items: Vec<Option<BatchItem>> = (0..count).map(|_| None).collect();
+        while let Some(joined) = tasks.join_next().await {
+            match joined {
+                Ok((index, item)) => items[index] = Some(item),
+                // タスク内のパニックは呼び出し元に伝える
+                Err(e) if e.is_panic() => std::panic::resume_unwind(e.into_panic()),
+                // タスクを中断するのはJoinSetのドロップ時のみ
+                Err(_) => unreachable!("batch task cancelled while awaited"),
+            }
+            if let Some(next) = pending.next() {
+                spawn(&mut tasks, next);
+            }
+        }
+
+        items.into_iter().flatten().collect()
+    }
+}
@@ -0,0 +1,344 @@
+/**
+ * ルート検索結果のキャッシュ
+ *
+ * このモジュールは`find_routers`の結果を一定時間キャッシュするラッパーを定義します。
+ * 同時に送信された同一のリクエストは1回のAPI呼び出しにまとめられ（シングルフライト）、
+ * 金額を丸めて近い金額のリクエスト同士でキャッシュを共有することもできます。
+ */
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use primitive_types::U256;
+use tokio::sync::OnceCell;
+
+use crate::client::AggregatorClientTrait;
+use crate::error::{AggregatorError, Result};
+use crate::models::{FindRouterParams, RouterData};
+
+/// API呼び出しの結果（エラーは同時に待機している呼び出し元で共有）
+type Outcome = std::result::Result<Option<RouterData>, Arc<AggregatorError>>;
+
+/// 金額の丸め方
+///
+/// 丸めた金額でAPIを呼び出すため、返される結果の金額は丸めた後の金額に対するものになります。
+/// 丸めた結果が0になる場合は元の金額を使用します。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AmountBucketing {
+    /// `step`の倍数に切り捨て
+    Step(U256),
+    /// 上位から指定した桁数（10進数）を残して切り捨て
+    ///
+    /// 例えば2桁の場合、`123456`は`120000`になります。金額の大きさに比例した幅で丸められます。
+    SignificantDigits(u32),
+}
+
+impl AmountBucketing {
+    /// 金額を丸める
+    pub fn apply(&self, amount: U256) -> U256 {
+        let bucketed = match *self {
+            Self::Step(step) if !step.is_zero() => amount / step * step,
+            Self::Step(_) => amount,
+            Self::SignificantDigits(digits) => {
+                let length = amount.to_string().len() as u32;
+                match length.checked_sub(digits.max(1)) {
+                    Some(drop) if drop > 0 => {
+                        let unit = U256::from(10u8).pow(U256::from(drop));
+                        amount / unit * unit
+                    }
+                    _ => amount,
+                }
+            }
+        };
+
+        if bucketed.is_zero() {
+            amount
+        } else {
+            bucketed
+        }
+    }
+}
+
+/// キャッシュの統計
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// キャッシュから返した回数
+    pub hits: u64,
+    /// APIを呼び出した回数
+    pub misses: u64,
+    /// 実行中の同一リクエストの結果を待って返した回数
+    pub coalesced: u64,
+    /// キャッシュされているエントリの数（期限切れを含む）
+    pub entries: usize,
+}
+
+impl CacheStats {
+    /// APIを呼び出さずに返した割合（呼び出しがない場合は0）
+    pub fn hit_rate(&self) -> f64 {
+        let total = self.hits + self.misses + self.coalesced;
+        if total == 0 {
+            0.0
+        } else {
+            (self.hits + self.coalesced) as f64 / total as f64
+        }
+    }
+}
+
+/// キャッシュされた結果
+struct CachedEntry {
+    data: Option<RouterData>,
+    expires_at: Instant,
+}
+
+#[derive(Default)]
+struct CacheState {
+    entries: HashMap<String, CachedEntry>,
+    in_flight: HashMap<String, Arc<OnceCell<Outcome>>>,
+}
+
+#[derive(Default)]
+struct CacheShared {
+    state: Mutex<CacheState>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    coalesced: AtomicU64,
+}
+
+impl CacheShared {
+    fn state(&self) -> MutexGuard<'_, CacheState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// API呼び出しが完了せずにキャンセルされた場合、待機している呼び出し元がいなければ実行中のエントリを取り除くガード
+///
+/// 待機している呼び出し元がいる場合は、そのうちの1つが代わりに実行するためエントリを残します。
+/// 取り除くと、代わりに実行している間に到着した呼び出し元が合流できず、APIを重複して呼び出してしまいます。
+struct InFlightGuard<'a> {
+    shared: &'a CacheShared,
+    key: &'a str,
+    cell: &'a Arc<OnceCell<Outcome>>,
+    completed: bool,
+}
+
+impl Drop for InFlightGuard<'_> {
+    fn drop(&mut self) {
+        if self.completed {
+            return;
+        }
+        let mut state = self.shared.state();
+        // エントリとこのガードの呼び出し元以外に参照がなければ、待機している呼び出し元はいない
+        if state.in_flight.get(self.key).is_some_and(|current| {
+            Arc::ptr_eq(current, self.cell)
+                && !current.initialized()
+                && Arc::strong_count(current) <= 2
+        }) {
+            state.in_flight.remove(self.key);
+        }
+    }
+}
+
+/// ルート検索結果をキャッシュするクライアント
+///
+/// 任意の[`AggregatorClientTrait`]の実装を包み、成功した結果を`ttl`の間キャッシュします。
+/// エラー（[`NoRouter`](AggregatorError::NoRouter)・[`InsufficientLiquidity`](AggregatorError::InsufficientLiquidity)のルートなしを含む）は
+/// キャッシュしません。同時に待機していた複数の呼び出し元でエラーを共有した場合は、
+/// [`AggregatorError::Shared`]として同じエラーを返します。
+/// キャッシュのキーには金額以外のパラメータ（深さ・分割方法・プロバイダーなど）もすべて含まれます。
+///
+/// クローンしたクライアントはキャッシュと統計を共有します。
+///
+/// ```no_run
+/// use std::time::Duration;
+///
+/// use cetus_aggregator_rust::cache::{AmountBucketing, QuoteCache};
+/// use cetus_aggregator_rust::AggregatorClient;
+///
+/// let client = QuoteCache::new(AggregatorClient::new(None), Duration::from_millis(500))
+///     .with_amount_bucketing(AmountBucketing::SignificantDigits(3));
+/// ```
+pub struct QuoteCache<C> {
+    client: Arc<C>,
+    ttl: Duration,
+    bucketing: Option<AmountBucketing>,
+    shared: Arc<CacheShared>,
+}
+
+impl<C> Clone for QuoteCache<C> {
+    fn clone(&self) -> Self {
+        Self {
+            client: Arc::clone(&self.client),
+            ttl: self.ttl,
+            bucketing: self.bucketing,
+            shared: Arc::clone(&self.shared),
+        }
+    }
+}
+
+impl<C> std::fmt::Debug for QuoteCache<C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("QuoteCache")
+            .field("ttl", &self.ttl)
+            .field("bucketing", &self.bucketing)
+            .field("stats", &self.stats())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<C> QuoteCache<C> {
+    /// 結果を`ttl`の間キャッシュするクライアントを作成
+    pub fn new(client: C, ttl: Duration) -> Self {
+        Self {
+            client: Arc::new(client),
+            ttl,
+            bucketing: None,
+            shared: Arc::new(CacheShared::default()),
+        }
+    }
+
+    /// 金額の丸め方を設定
+    pub fn with_amount_bucketing(mut self, bucketing: AmountBucketing) -> Self {
+        self.bucketing = Some(bucketing);
+        self
+    }
+
+    /// 包んでいるクライアント
+    pub fn inner(&self) -> &C {
+        &self.client
+    }
+
+    /// エントリの有効期間
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// 金額の丸め方
+    pub fn amount_bucketing(&self) -> Option<AmountBucketing> {
+        self.bucketing
+    }
+
+    fn state(&self) -> MutexGuard<'_, CacheState> {
+        self.shared.state()
+    }
+
+    /// キャッシュの統計を取得
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.shared.hits.load(Ordering::Relaxed),
+            misses: self.shared.misses.load(Ordering::Relaxed),
+            coalesced: self.shared.coalesced.load(Ordering::Relaxed),
+            entries: self.state().entries.len(),
+        }
+    }
+
+    /// キャッシュをすべて消去（統計は消去しない）
+    pub fn clear(&self) {
+        self.state().entries.clear();
+    }
+
+    /// 指定したパラメータのエントリを消去
+    pub fn invalidate(&self, params: &FindRouterParams) {
+        let (_, key) = self.cache_key(params.clone());
+        self.state().entries.remove(&key);
+    }
+
+    /// 金額を丸めたパラメータとキャッシュのキーを作成
+    fn cache_key(&self, mut params: FindRouterParams) -> (FindRouterParams, String) {
+        if let Some(bucketing) = self.bucketing {
+            params.amount = bucketing.apply(params.amount);
+        }
+        // シリアライズ結果にはAPIに送信するすべてのパラメータが含まれる
+        let key = serde_json::to_string(&params).unwrap_or_else(|_| format!("{:?}", params));
+        (params, key)
+    }
+}
+
+#[async_trait]
+impl<C> AggregatorClientTrait for QuoteCache<C>
+where
+    C: AggregatorClientTrait + Send + Sync,
+{
+    async fn find_routers(&self, params: FindRouterParams) -> Result<Option<RouterData>> {
+        let (params, key) = self.cache_key(params);
+
+        let cell = {
+            let mut state = self.state();
+            let now = Instant::now();
+            if let Some(entry) = state
+                .entries
+                .get(&key)
+                .filter(|entry| entry.expires_at > now)
+            {
+                self.shared.hits.fetch_add(1, Ordering::Relaxed);
+                return Ok(entry.data.clone());
+            }
+
+            Arc::clone(
+                state
+                    .in_flight
+                    .entry(key.clone())
+                    .or_insert_with(|| Arc::new(OnceCell::new())),
+            )
+        };
+
+        // 最初に実行した呼び出し元がキャンセルされた場合は、待機していた呼び出し元が代わりに実行する。
+        // APIを呼び出した呼び出し元をミス、結果を待っただけの呼び出し元を合流として数える
+        let mut called = false;
+        let outcome = cell
+            .get_or_init(|| {
+                called = true;
+                self.shared.misses.fetch_add(1, Ordering::Relaxed);
+                async {
+                    let mut guard = InFlightGuard {
+                        shared: &self.shared,
+                        key: &key,
+                        cell: &cell,
+                        completed: false,
+                    };
+                    let outcome = self.client.find_routers(params).await.map_err(Arc::new);
+                    guard.completed = true;
+                    outcome
+                }
+            })
+            .await;
+        if !called {
+            self.shared.coalesced.fetch_add(1, Ordering::Relaxed);
+        }
+
+        {
+            let mut state = self.state();
+            if state
+                .in_flight
+                .get(&key)
+                .is_some_and(|current| Arc::ptr_eq(current, &cell))
+            {
+                state.in_flight.remove(&key);
+                if let Ok(data) = outcome {
+                    let now = Instant::now();
+                    state.entries.retain(|_, entry| entry.expires_at > now);
+                    state.entries.insert(
+                        key,
+                        CachedEntry {
+                            data: data.clone(),
+                            expires_at: now + self.ttl,
+                        },
+                    );
+                }
+            }
+        }
+
+        match outcome {
+            Ok(data) => Ok(data.clone()),
+            Err(error) => {
+                let error = Arc::clone(error);
+                drop(cell);
+                // 他に待機している呼び出し元がいなければ元のエラーをそのまま返す
+                Err(Arc::try_unwrap(error).unwrap_or_else(AggregatorError::Shared))
+            }
+        }
+    }
+}
@@ -0,0 +1,370 @@
+/**
+ * HTTP通信の記録と再生（カセット）
+ *
+ * このモジュールはクライアントが送受信したリクエストとレスポンスの組をJSONファイル（カセット）に記録し、
+ * ネットワークに接続せずに再生する機能を提供します。
+ * 実際に取得したデータを使って、ルート選択などの回帰テストを決定的に実行できます。
+ */
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::error::{AggregatorError, Result};
+
+/// 記録しないレスポンスヘッダー（再生時の本文と一致しなくなるため）
+const SKIPPED_HEADERS: &[&str] = &["connection", "content-length", "transfer-encoding"];
+
+/// カセットの動作モード
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CassetteMode {
+    /// 実際にリクエストを送信し、リクエストとレスポンスの組をファイルに記録
+    Record,
+    /// 記録済みのレスポンスを返し、ネットワークには接続しない
+    Replay,
+}
+
+/// 記録したリクエスト
+///
+/// 照合にはメソッド・パス・クエリパラメータ・本文を使用し、
+/// エンドポイントのホスト名は使用しません。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CassetteRequest {
+    /// HTTPメソッド
+    pub method: String,
+    /// URLのパス
+    pub path: String,
+    /// デコード済みのクエリパラメータ
+    #[serde(default)]
+    pub query: BTreeMap<String, String>,
+    /// JSONとして解析した本文（本文がない場合は`None`）
+    #[serde(default)]
+    pub body: Option<Value>,
+}
+
+impl CassetteRequest {
+    /// 送信するリクエストから作成
+    pub(crate) fn new(request: &reqwest::Request) -> Self {
+        let url = request.url();
+        let body = request
+            .body()
+            .and_then(|body| body.as_bytes())
+            .map(|bytes| {
+                serde_json::from_slice(bytes)
+                    .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(bytes).into_owned()))
+            });
+
+        Self {
+            method: request.method().to_string(),
+            path: url.path().to_string(),
+            query: url.query_pairs().into_owned().collect(),
+            body,
+        }
+    }
+
+    /// 照合に失敗した場合にエラーに含めるURL（パスとクエリ）
+    fn display_url(&self) -> String {
+        let query: Vec<String> = self
+            .query
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect();
+        if query.is_empty() {
+            self.path.clone()
+        } else {
+            format!("{}?{}", self.path, query.join("&"))
+        }
+    }
+}
+
+/// 記録したレスポンス
+///
+/// 本文はJSONとして解析できる場合は`body`に、できない場合は`text`にそのまま記録します。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CassetteResponse {
+    /// HTTPステータスコード
+    pub status: u16,
+    /// レスポンスヘッダー（名前は小文字）
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    /// JSONとして解析した本文
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<Value>,
+    /// JSONとして解析できない本文
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+}
+
+impl CassetteResponse {
+    fn new(response: &HttpResponse) -> Self {
+        let headers = response
+            .headers
+            .iter()
+            .filter(|(name, _)| !SKIPPED_HEADERS.contains(&name.as_str()))
+            .filter_map(|(name, value)| {
+                Some((name.as_str().to_string(), value.to_str().ok()?.to_string()))
+            })
+            .collect();
+        let (body, text) = match serde_json::from_slice(&response.body) {
+            Ok(body) => (Some(body), None),
+            Err(_) => (
+                None,
+                Some(String::from_utf8_lossy(&response.body).into_owned()),
+            ),
+        };
+
+        Self {
+            status: response.status.as_u16(),
+            headers,
+            body,
+            text,
+        }
+    }
+
+    fn to_http_response(&self) -> HttpResponse {
+        let headers = self
+            .headers
+            .iter()
+            .filter_map(|(name, value)| {
+                Some((
+                    HeaderName::from_bytes(name.as_bytes()).ok()?,
+                    HeaderValue::from_str(value).ok()?,
+                ))
+            })
+            .collect();
+        let body = match (&self.body, &self.text) {
+            (Some(body), _) => body.to_string().into_bytes(),
+            (None, Some(text)) => text.clone().into_bytes(),
+            (None, None) => Vec::new(),
+        };
+
+        HttpResponse {
+            status: StatusCode::from_u16(self.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            headers,
+            body,
+        }
+    }
+}
+
+/// リクエストとレスポンスの組
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Interaction {
+    /// リクエスト
+    pub request: CassetteRequest,
+    /// レスポンス
+    pub response: CassetteResponse,
+}
+
+/// カセットファイルの内容
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CassetteFile {
+    interactions: Vec<Interaction>,
+}
+
+/// 受信したHTTPレスポンス（本文は読み込み済み）
+#[derive(Debug)]
+pub(crate) struct HttpResponse {
+    pub(crate) status: StatusCode,
+    pub(crate) headers: HeaderMap,
+    pub(crate) body: Vec<u8>,
+}
+
+#[derive(Debug, Default)]
+struct CassetteState {
+    interactions: Vec<Interaction>,
+    /// 再生済みかどうか（`interactions`と同じ順序）
+    played: Vec<bool>,
+    /// ファイルに書き込んでいない記録があるかどうか
+    unsaved: bool,
+}
+
+/// リクエストとレスポンスの組を記録・再生するカセット
+///
+/// 記録モードでは、受信したレスポンスをメモリに蓄積し、[`save`](Self::save)を呼び出したとき、
+/// またはカセットがドロップされたときにファイルへ書き込みます。
+/// リクエストのたびにファイルを書き直さないため、記録中もリクエストの処理を妨げません。
+/// 再生モードでは、一致するリクエストのうちまだ再生していないものを記録順に返し、
+/// すべて再生済みの場合は最後に一致したものを繰り返し返します。
+/// 一致するリクエストがない場合は[`AggregatorError::CassetteMismatch`]を返します。
+#[derive(Debug)]
+pub struct Cassette {
+    path: PathBuf,
+    mode: CassetteMode,
+    state: Mutex<CassetteState>,
+}
+
+impl Cassette {
+    /// 記録モードのカセットを作成
+    ///
+    /// 既存のファイルは、記録したレスポンスを最初に書き込んだ時点で上書きされます。
+    pub fn record(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            mode: CassetteMode::Record,
+            state: Mutex::new(CassetteState::default()),
+        }
+    }
+
+    /// 再生モードのカセットをファイルから読み込み
+    pub fn replay(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let file: CassetteFile = std::fs::read(&path)
+            .map_err(|e| cassette_error(&path, e))
+            .and_then(|bytes| {
+                serde_json::from_slice(&bytes).map_err(|e| cassette_error(&path, e))
+            })?;
+
+        Ok(Self {
+            path,
+            mode: CassetteMode::Replay,
+            state: Mutex::new(CassetteState {
+                played: vec![false; file.interactions.len()],
+                interactions: file.interactions,
+                unsaved: false,
+            }),
+        })
+    }
+
+    /// ファイルが存在する場合は再生モード、存在しない場合は記録モードのカセットを作成
+    pub fn replay_or_record(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        if path.exists() {
+            Self::replay(path)
+        } else {
+            Ok(Self::record(path))
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, CassetteState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// カセットファイルのパス
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 動作モード
+    pub fn mode(&self) -> CassetteMode {
+        self.mode
+    }
+
+    /// 記録済みのリクエストとレスポンスの組
+    pub fn interactions(&self) -> Vec<Interaction> {
+        self.state().interactions.clone()
+    }
+
+    /// 再生モードで一度も再生されていない組
+    ///
+    /// テストの最後に空であることを確認すると、記録時と同じリクエストが送信されたかを検証できます。
+    pub fn unplayed(&self) -> Vec<Interaction> {
+        let state = self.state();
+        state
+            .interactions
+            .iter()
+            .zip(&state.played)
+            .filter(|(_, played)| !**played)
+            .map(|(interaction, _)| interaction.clone())
+            .collect()
+    }
+
+    /// 記録したレスポンスを返す（再生モード）
+    pub(crate) fn play(&self, request: &CassetteRequest) -> Result<HttpResponse> {
+        let mut state = self.state();
+        let matching: Vec<usize> = state
+            .interactions
+            .iter()
+            .enumerate()
+            .filter(|(_, interaction)| interaction.request == *request)
+            .map(|(index, _)| index)
+            .collect();
+
+        let index = matching
+            .iter()
+            .copied()
+            .find(|index| !state.played[*index])
+            .or_else(|| matching.last().copied())
+            .ok_or_else(|| AggregatorError::CassetteMismatch {
+                method: request.method.clone(),
+                url: request.display_url(),
+            })?;
+
+        state.played[index] = true;
+        Ok(state.interactions[index].response.to_http_response())
+    }
+
+    /// リクエストとレスポンスの組をメモリに記録する（記録モード）
+    pub(crate) fn record_interaction(&self, request: CassetteRequest, response: &HttpResponse) {
+        let mut state = self.state();
+        state.interactions.push(Interaction {
+            request,
+            response: CassetteResponse::new(response),
+        });
+        state.played.push(true);
+        state.unsaved = true;
+    }
+
+    /// 記録したリクエストとレスポンスの組をファイルに書き込む（記録モード）
+    ///
+    /// ドロップ時にも書き込みますが、書き込みのエラーを確認する場合は明示的に呼び出してください。
+    /// 再生モードの場合、および新しい記録がない場合は何もしません。
+    pub fn save(&self) -> Result<()> {
+        if self.mode != CassetteMode::Record {
+            return Ok(());
+        }
+
+        // ファイルの書き込み中にロックを保持しないよう、内容を複製してから書き込む
+        let file = {
+            let mut state = self.state();
+            if !state.unsaved {
+                return Ok(());
+            }
+            state.unsaved = false;
+            CassetteFile {
+                interactions: state.interactions.clone(),
+            }
+        };
+
+        let result = self.write(&file);
+        if result.is_err() {
+            self.state().unsaved = true;
+        }
+        result
+    }
+
+    fn write(&self, file: &CassetteFile) -> Result<()> {
+        let json = serde_json::to_vec_pretty(file)?;
+        if let Some(parent) = self
+            .path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            std::fs::create_dir_all(parent).map_err(|e| cassette_error(&self.path, e))?;
+        }
+        std::fs::write(&self.path, json).map_err(|e| cassette_error(&self.path, e))
+    }
+}
+
+/// 書き込んでいない記録をファイルに書き込む（エラーは無視されます）
+impl Drop for Cassette {
+    fn drop(&mut self) {
+        let _ = self.save();
+    }
+}
+
+/// カセットファイルの読み書きのエラーを作成
+fn cassette_error(
+    path: &Path,
+    source: impl Into<Box<dyn std::error::Error + Send + Sync>>,
+) -> AggregatorError {
+    AggregatorError::CassetteError {
+        path: path.to_path_buf(),
+        source: source.into(),
+    }
+}
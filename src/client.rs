@@ -3,21 +3,144 @@
  *
  * このモジュールはCetus Aggregator APIと通信するためのクライアントを実装します。
  */
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
 use async_trait::async_trait;
-use reqwest::Client as HttpClient;
-use serde_json::json;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::{Client as HttpClient, Proxy, Url};
+use serde_json::{json, Map, Value};
 
-use crate::error::{AggregatorError, Result};
-use crate::models::{AggregatorResponse, FindRouterParams, RouterData};
+use crate::cassette::{Cassette, CassetteMode, CassetteRequest, HttpResponse};
+use crate::coin::CoinType;
+use crate::endpoint::{
+    CircuitBreaker, Endpoint, EndpointSet, EndpointStatus, RequestStrategy,
+    DEFAULT_RECOVERY_INTERVAL, DEFAULT_UNHEALTHY_THRESHOLD,
+};
+use crate::error::{
+    json_error_snippet, parse_retry_after, truncate_body, AggregatorError,
+    AggregatorServerErrorCode, HttpExchange, RequestContext, Result,
+};
+use crate::limit::{Limiter, LimiterStatus, Priority, RateLimit};
+use crate::locale::{global_locale, tr, Locale};
+use crate::models::{AggregatorResponse, FindRouterParams, ParamLimits, RouterData};
+use crate::package::{Network, PackageAllowlist, PackagePolicy, PackageVerification};
+use crate::retry::RetryPolicy;
 
 /// コイン識別子を完全な形式に変換する関数
 ///
-/// この関数は、短縮形式のコイン識別子を完全な形式に変換します。
-/// 現在の実装では単純に入力をそのまま返しますが、将来的には必要に応じて拡張できます。
-fn completion_coin(coin: &str) -> String {
-    // 現在の実装では単純に入力をそのまま返す
-    // 実際のTypeScript実装に合わせて拡張する必要がある場合は、ここを修正
-    coin.to_string()
+/// この関数は、短縮形式のコイン識別子（`0x2::sui::SUI`など）を、
+/// 型引数を含むすべてのアドレスを64桁に展開した完全な形式に変換します。
+fn completion_coin(coin: &CoinType) -> String {
+    coin.to_long_string()
+}
+
+/// GETとPOSTで共通のリクエストパラメータ
+///
+/// GETではURLエンコードしたクエリパラメータ、POSTではJSONの本文として送信します。
+/// 流動性変更はPOSTの本文にのみ含めるため、ここには含めません。
+fn request_params(params: &FindRouterParams) -> Vec<(&'static str, Value)> {
+    let mut fields = vec![
+        ("from", json!(completion_coin(&params.from))),
+        ("target", json!(completion_coin(&params.target))),
+        ("amount", json!(params.amount.to_string())),
+        ("by_amount_in", json!(params.by_amount_in)),
+    ];
+
+    if let Some(depth) = params.depth {
+        fields.push(("depth", json!(depth)));
+    }
+
+    if let Some(ref split) = params.split {
+        if let Some(name) = split.name() {
+            fields.push(("split_algorithm", json!(name)));
+        }
+        if let Some(factor) = split.factor() {
+            fields.push(("split_factor", json!(factor)));
+        }
+        if let Some(count) = split.count() {
+            fields.push(("split_count", json!(count)));
+        }
+    }
+
+    // プロバイダーリストをカンマ区切り文字列に変換
+    if let Some(providers) = params.providers.to_query_value() {
+        fields.push(("providers", json!(providers)));
+    }
+
+    fields
+}
+
+/// 別のエンドポイントに送信すれば成功する可能性のあるエラーかどうか
+///
+/// 再試行可能なエラーに加えて、サーキットブレーカーで送信しなかった場合も次のエンドポイントを試します。
+fn should_try_next(error: &AggregatorError) -> bool {
+    error.is_retryable() || matches!(error, AggregatorError::CircuitOpen { .. })
+}
+
+/// サーバーが確定的に応答したことを示すエラーかどうか
+///
+/// 4xxのHTTPステータスやサーバーのエラーコードはエンドポイントが正常に応答した結果のため、
+/// サーキットブレーカーでは成功として扱います。
+fn is_server_answer(error: &AggregatorError) -> bool {
+    match error {
+        AggregatorError::HttpError { status, .. } => (400..500).contains(status),
+        AggregatorError::ApiError { .. }
+        | AggregatorError::NumberTooLarge { .. }
+        | AggregatorError::NoRouter { .. }
+        | AggregatorError::InsufficientLiquidity { .. }
+        | AggregatorError::HoneyPot { .. } => true,
+        AggregatorError::Shared(e) => is_server_answer(e),
+        _ => false,
+    }
+}
+
+/// ルーターデータに含まれるエラーと流動性不足をエラーに変換
+///
+/// サーバーは`code`を200のまま、`data.error`や`insufficient_liquidity`でエラーを返す場合があります。
+/// モッククライアントも実際のクライアントと同じ結果を返すよう、この関数で変換します。
+pub(crate) fn check_router_data(
+    route_data: RouterData,
+    context: impl FnOnce() -> RequestContext,
+) -> Result<RouterData> {
+    if let Some(error) = route_data.error.as_ref().filter(|error| error.code != 0) {
+        return Err(AggregatorError::from_server_code(
+            error.code,
+            error.msg.clone(),
+            context(),
+        ));
+    }
+    if route_data.insufficient_liquidity {
+        return Err(AggregatorError::from_server_code(
+            AggregatorServerErrorCode::InsufficientLiquidity as u32,
+            route_data
+                .error
+                .map(|error| error.msg)
+                .unwrap_or_else(|| "insufficient_liquidity".to_string()),
+            context(),
+        ));
+    }
+    Ok(route_data)
+}
+
+/// エラーに含めるために保持する送信済みリクエストの概要
+struct SentRequest {
+    method: String,
+    url: String,
+    body: Option<String>,
+}
+
+impl SentRequest {
+    fn new(request: &reqwest::Request) -> Self {
+        Self {
+            method: request.method().to_string(),
+            url: request.url().to_string(),
+            body: request
+                .body()
+                .and_then(|body| body.as_bytes())
+                .map(truncate_body),
+        }
+    }
 }
 
 /// アグリゲーターAPIクライアントのトレイト
@@ -35,13 +158,49 @@ pub trait AggregatorClientTrait {
     async fn find_routers(&self, params: FindRouterParams) -> Result<Option<RouterData>>;
 }
 
+/// デフォルトのAPIエンドポイント
+pub const DEFAULT_ENDPOINT: &str = "https://api-sui.cetus.zone/router_v2";
+
+/// デフォルトのルート検索APIのパス（エンドポイントからの相対パス）
+pub const DEFAULT_API_PATH: &str = "find_routes";
+
+/// デフォルトでリクエストに付与するSDKバージョン
+pub const DEFAULT_SDK_VERSION: &str = "1000327";
+
+/// APIキーを送信する際のヘッダー名
+pub const API_KEY_HEADER: &str = "x-api-key";
+
 /// アグリゲーターAPIクライアント実装
+///
+/// 内部の`reqwest::Client`はコネクションプールを共有するため、
+/// クローンしたクライアント同士も同じプールを利用します。
+/// エンドポイントの健全性の統計もクローン間で共有されます。
 #[derive(Debug, Clone)]
 pub struct AggregatorClient {
-    /// APIエンドポイント
-    pub endpoint: String,
+    /// APIエンドポイントの一覧
+    endpoints: Arc<EndpointSet>,
+    /// 複数エンドポイントへのリクエスト方式
+    strategy: RequestStrategy,
     /// HTTPクライアント
     http_client: HttpClient,
+    /// リクエストごとに付与するヘッダー（APIキー・ユーザーエージェントを含む）
+    headers: HeaderMap,
+    /// リトライポリシー
+    retry_policy: RetryPolicy,
+    /// パッケージの許可リストによる検証
+    package_verification: Option<Arc<PackageVerification>>,
+    /// パラメータの検証で使用する上限
+    param_limits: ParamLimits,
+    /// リクエストに付与するSDKバージョン
+    sdk_version: String,
+    /// ルート検索APIのパス
+    api_path: String,
+    /// メッセージの言語（`None`の場合はプロセス全体のロケール）
+    locale: Option<Locale>,
+    /// 通信を記録・再生するカセット
+    cassette: Option<Arc<Cassette>>,
+    /// レート制限と同時実行数の制限（クローン間で共有）
+    limiter: Option<Arc<Limiter>>,
 }
 
 impl AggregatorClient {
@@ -55,114 +214,231 @@ impl AggregatorClient {
     ///
     /// 新しいAggregatorClientインスタンス
     pub fn new(endpoint: Option<String>) -> Self {
-        let default_endpoint = "https://api-sui.cetus.zone/router_v2".to_string();
-        let endpoint = endpoint.unwrap_or(default_endpoint);
+        let endpoint = endpoint.unwrap_or_else(|| DEFAULT_ENDPOINT.to_string());
 
         Self {
-            endpoint,
+            endpoints: Arc::new(EndpointSet::new(
+                vec![endpoint],
+                DEFAULT_UNHEALTHY_THRESHOLD,
+                DEFAULT_RECOVERY_INTERVAL,
+                None,
+            )),
+            strategy: RequestStrategy::default(),
             http_client: HttpClient::new(),
+            headers: HeaderMap::new(),
+            retry_policy: RetryPolicy::none(),
+            package_verification: None,
+            param_limits: ParamLimits::default(),
+            sdk_version: DEFAULT_SDK_VERSION.to_string(),
+            api_path: DEFAULT_API_PATH.to_string(),
+            locale: None,
+            cassette: None,
+            limiter: None,
         }
     }
 
-    /// GETリクエストによるルート検索
-    ///
-    /// # 引数
+    /// クライアントビルダーを作成
     ///
-    /// * `params` - ルート検索のパラメータ
-    ///
-    /// # 戻り値
+    /// タイムアウト、デフォルトヘッダー、プロキシ、TLSバックエンドの指定や、
+    /// 既存の`reqwest::Client`の注入を行う場合に使用します。
+    pub fn builder() -> AggregatorClientBuilder {
+        AggregatorClientBuilder::default()
+    }
+
+    /// 最優先（設定順で最初）のAPIエンドポイントを取得
+    pub fn endpoint(&self) -> &str {
+        &self.endpoints.primary().url
+    }
+
+    /// 設定されているすべてのAPIエンドポイントを設定順で取得
+    pub fn endpoints(&self) -> Vec<String> {
+        self.endpoints.urls()
+    }
+
+    /// 各エンドポイントの健全性を取得
+    pub fn endpoint_status(&self) -> Vec<EndpointStatus> {
+        self.endpoints.status()
+    }
+
+    /// 複数エンドポイントへのリクエスト方式を取得
+    pub fn request_strategy(&self) -> RequestStrategy {
+        self.strategy
+    }
+
+    /// 内部で使用しているHTTPクライアントを取得
+    pub fn http_client(&self) -> &HttpClient {
+        &self.http_client
+    }
+
+    /// リトライポリシーを取得
+    pub fn retry_policy(&self) -> &RetryPolicy {
+        &self.retry_policy
+    }
+
+    /// リトライポリシーを変更したクライアントを返す
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// SDKバージョンを取得
+    pub fn sdk_version(&self) -> &str {
+        &self.sdk_version
+    }
+
+    /// ルート検索APIのパスを取得
+    pub fn api_path(&self) -> &str {
+        &self.api_path
+    }
+
+    /// このクライアントのメッセージの言語を取得
     ///
-    /// 成功した場合はHTTPレスポンス、失敗した場合はエラーを返します。
-    async fn get_router(&self, params: &FindRouterParams) -> Result<reqwest::Response> {
-        let from_coin = completion_coin(&params.from);
-        let target_coin = completion_coin(&params.target);
+    /// クライアントに設定されていない場合はプロセス全体のロケールを返します。
+    /// エラーをこの言語で表示するには`error.localized(client.locale())`を使用します。
+    pub fn locale(&self) -> Locale {
+        self.locale.unwrap_or_else(global_locale)
+    }
 
-        // URLの基本部分を構築
-        let mut url = format!(
-            "{}/find_routes?from={}&target={}&amount={}&by_amount_in={}",
-            self.endpoint, from_coin, target_coin, params.amount, params.by_amount_in
-        );
+    /// メッセージの言語を変更したクライアントを返す
+    pub fn with_locale(mut self, locale: Locale) -> Self {
+        self.locale = Some(locale);
+        self
+    }
 
-        // オプションパラメータを追加
-        if let Some(depth) = params.depth {
-            url.push_str(&format!("&depth={}", depth));
-        }
+    /// 通信を記録・再生するカセット（設定されていない場合は`None`）
+    pub fn cassette(&self) -> Option<&Cassette> {
+        self.cassette.as_deref()
+    }
 
-        if let Some(ref split_algorithm) = params.split_algorithm {
-            url.push_str(&format!("&split_algorithm={}", split_algorithm));
-        }
+    /// レート制限と同時実行数の制限の状態（制限が設定されていない場合は`None`）
+    pub fn limiter_status(&self) -> Option<LimiterStatus> {
+        self.limiter.as_ref().map(|limiter| limiter.status())
+    }
 
-        if let Some(split_factor) = params.split_factor {
-            url.push_str(&format!("&split_factor={}", split_factor));
-        }
+    /// 同時に送信するリクエスト数の上限（設定されていない場合は`None`）
+    pub(crate) fn max_in_flight(&self) -> Option<usize> {
+        self.limiter
+            .as_ref()
+            .and_then(|limiter| limiter.max_in_flight())
+    }
 
-        if let Some(split_count) = params.split_count {
-            url.push_str(&format!("&split_count={}", split_count));
-        }
+    /// 優先度を指定してルート検索を実行
+    ///
+    /// レート制限または同時実行数の制限を待つ場合、優先度の高いリクエストから順に送信されます。
+    /// [`AggregatorClientTrait::find_routers`]は[`Priority::Normal`]で実行します。
+    pub async fn find_routers_with_priority(
+        &self,
+        params: FindRouterParams,
+        priority: Priority,
+    ) -> Result<Option<RouterData>> {
+        // 不正なパラメータはHTTPリクエストを送信する前に拒否
+        params.validate_in(&self.param_limits, self.locale())?;
 
-        if let Some(ref providers) = params.providers {
-            if !providers.is_empty() {
-                url.push_str(&format!("&providers={}", providers.join(",")));
+        let mut attempt = 0;
+        loop {
+            match self.find_routers_once(&params, priority).await {
+                // 再試行可能なエラーはポリシーに従って待機してから再送
+                Err(e) if attempt < self.retry_policy.max_retries && e.is_retryable() => {
+                    // サーバーが上限より長い待機を求めた場合は元のエラーを返す
+                    let Some(delay) = self.retry_policy.delay_for(attempt, &e) else {
+                        return Err(e);
+                    };
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                result => return result,
             }
         }
+    }
 
-        // SDK バージョンを追加
-        url.push_str("&v=1000327");
+    /// ルート検索APIのURLを構築
+    ///
+    /// エンドポイントのパスの末尾にAPIのパスを追加し、SDKバージョンをクエリパラメータとして付与します。
+    fn find_routes_url(&self, endpoint: &str) -> Result<Url> {
+        let locale = self.locale();
+        let mut url = Url::parse(endpoint).map_err(|e| {
+            AggregatorError::InputError(tr!(
+                locale =>
+                "不正なエンドポイントです ({}): {}",
+                "invalid endpoint ({}): {}",
+                e,
+                endpoint
+            ))
+        })?;
 
-        // HTTPリクエストを実行
-        match self.http_client.get(&url).send().await {
-            Ok(response) => Ok(response),
-            Err(e) => Err(AggregatorError::RequestError(e)),
+        url.path_segments_mut()
+            .map_err(|_| {
+                AggregatorError::InputError(tr!(
+                locale =>
+                    "不正なエンドポイントです: {}",
+                    "invalid endpoint: {}",
+                    endpoint
+                ))
+            })?
+            .pop_if_empty()
+            .extend(
+                self.api_path
+                    .split('/')
+                    .filter(|segment| !segment.is_empty()),
+            );
+        url.query_pairs_mut().append_pair("v", &self.sdk_version);
+
+        Ok(url)
+    }
+
+    /// GETリクエストによるルート検索のリクエストを構築
+    ///
+    /// # 引数
+    ///
+    /// * `endpoint` - 送信先のAPIエンドポイント
+    /// * `params` - ルート検索のパラメータ
+    ///
+    /// # 戻り値
+    ///
+    /// 成功した場合はHTTPリクエスト、失敗した場合はエラーを返します。
+    fn get_router(&self, endpoint: &str, params: &FindRouterParams) -> Result<reqwest::Request> {
+        let mut url = self.find_routes_url(endpoint)?;
+
+        // パラメータはURLエンコードしてクエリに追加
+        {
+            let mut query = url.query_pairs_mut();
+            for (name, value) in request_params(params) {
+                match value {
+                    Value::String(s) => query.append_pair(name, &s),
+                    other => query.append_pair(name, &other.to_string()),
+                };
+            }
         }
+
+        Ok(self
+            .http_client
+            .get(url)
+            .headers(self.headers.clone())
+            .build()?)
     }
 
-    /// POSTリクエストによる流動性変更付きルート検索
+    /// POSTリクエストによる流動性変更付きルート検索のリクエストを構築
     ///
     /// # 引数
     ///
+    /// * `endpoint` - 送信先のAPIエンドポイント
     /// * `params` - ルート検索のパラメータ（流動性変更を含む）
     ///
     /// # 戻り値
     ///
-    /// 成功した場合はHTTPレスポンス、失敗した場合はエラーを返します。
-    async fn post_router_with_liquidity_changes(
+    /// 成功した場合はHTTPリクエスト、失敗した場合はエラーを返します。
+    fn post_router_with_liquidity_changes(
         &self,
+        endpoint: &str,
         params: &FindRouterParams,
-    ) -> Result<reqwest::Response> {
-        let from_coin = completion_coin(&params.from);
-        let target_coin = completion_coin(&params.target);
-        let url = format!("{}/find_routes", self.endpoint);
-
-        // プロバイダーリストをカンマ区切り文字列に変換
-        let providers_str = params.providers.as_ref().map(|p| p.join(","));
+    ) -> Result<reqwest::Request> {
+        let url = self.find_routes_url(endpoint)?;
 
         // リクエストデータを構築
-        let mut request_data = json!({
-            "from": from_coin,
-            "target": target_coin,
-            "amount": params.amount.to_string(),
-            "by_amount_in": params.by_amount_in,
-        });
-
-        if let Some(depth) = params.depth {
-            request_data["depth"] = json!(depth);
-        }
-
-        if let Some(ref split_algorithm) = params.split_algorithm {
-            request_data["split_algorithm"] = json!(split_algorithm);
-        }
-
-        if let Some(split_factor) = params.split_factor {
-            request_data["split_factor"] = json!(split_factor);
-        }
-
-        if let Some(split_count) = params.split_count {
-            request_data["split_count"] = json!(split_count);
-        }
-
-        if let Some(ref providers_str) = providers_str {
-            request_data["providers"] = json!(providers_str);
-        }
+        let mut request_data: Map<String, Value> = request_params(params)
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value))
+            .collect();
 
         // 流動性変更データを追加
         if let Some(ref liquidity_changes) = params.liquidity_changes {
@@ -178,69 +454,820 @@ impl AggregatorClient {
                 })
                 .collect::<Vec<_>>();
 
-            request_data["liquidity_changes"] = json!(changes);
+            request_data.insert("liquidity_changes".to_string(), json!(changes));
         }
 
-        // POSTリクエストを送信
-        match self.http_client.post(&url).json(&request_data).send().await {
-            Ok(response) => Ok(response),
-            Err(e) => Err(AggregatorError::RequestError(e)),
-        }
+        Ok(self
+            .http_client
+            .post(url)
+            .headers(self.headers.clone())
+            .json(&request_data)
+            .build()?)
     }
 
     /// レスポンスを解析してルーターデータを取得
     ///
     /// # 引数
     ///
-    /// * `response` - HTTPレスポンス
+    /// * `response` - 本文を読み込み済みのHTTPレスポンス
+    /// * `request` - 送信したリクエストの概要（エラーに含めるため）
+    /// * `context` - 送信したルート検索の内容（サーバーのエラーに含めるため）
     ///
     /// # 戻り値
     ///
     /// 成功した場合はルーターデータを含むOption、失敗した場合はエラーを返します。
-    async fn parse_router_response(
+    fn parse_router_response(
         &self,
-        response: reqwest::Response,
+        response: HttpResponse,
+        request: SentRequest,
+        context: impl FnOnce() -> RequestContext,
     ) -> Result<Option<RouterData>> {
+        let HttpResponse {
+            status,
+            headers,
+            body,
+        } = response;
+        let exchange = || {
+            Box::new(HttpExchange {
+                method: request.method,
+                url: request.url,
+                request_body: request.body,
+                status: status.as_u16(),
+                headers: headers.clone(),
+                body: truncate_body(&body),
+                body_len: body.len(),
+            })
+        };
+
         // レスポンスが成功したか確認
-        if !response.status().is_success() {
-            return Err(AggregatorError::ApiError {
-                code: response.status().as_u16() as u32,
-                message: format!("APIエラー: {}", response.status()),
+        if !status.is_success() {
+            let retry_after = headers
+                .get(reqwest::header::RETRY_AFTER)
+                .and_then(|value| value.to_str().ok())
+                .and_then(parse_retry_after);
+
+            return Err(AggregatorError::HttpError {
+                status: status.as_u16(),
+                message: tr!(self.locale() => "APIエラー: {}", "API error: {}", status),
+                retry_after,
+                exchange: Some(exchange()),
             });
         }
 
         // レスポンス本文をJSONとして解析
-        let data: AggregatorResponse = match response.json().await {
+        let data: AggregatorResponse = match serde_json::from_slice(&body) {
             Ok(data) => data,
-            Err(e) => return Err(AggregatorError::RequestError(e)),
+            Err(source) => {
+                return Err(AggregatorError::DecodeError {
+                    snippet: json_error_snippet(&body, &source),
+                    source,
+                    exchange: exchange(),
+                })
+            }
         };
 
         // エラーチェック
         if data.code != 0 && data.code != 200 {
-            return Err(AggregatorError::ApiError {
-                code: data.code,
-                message: data.msg,
-            });
+            return Err(AggregatorError::from_server_code(
+                data.code,
+                data.msg,
+                context(),
+            ));
         }
 
-        // ルーターデータを返却
-        Ok(data.data)
+        let Some(route_data) = data.data else {
+            return Ok(None);
+        };
+
+        // ルーターデータに含まれるエラーと流動性不足もエラーとして返す
+        check_router_data(route_data, context).map(Some)
     }
-}
 
-#[async_trait]
-impl AggregatorClientTrait for AggregatorClient {
-    async fn find_routers(&self, params: FindRouterParams) -> Result<Option<RouterData>> {
+    /// リクエストを送信し、レスポンスの本文まで読み込む
+    ///
+    /// カセットが再生モードの場合は送信せずに記録済みのレスポンスを返し、
+    /// 記録モードの場合は受信したレスポンスをカセットに記録します。
+    async fn execute(&self, request: reqwest::Request) -> Result<HttpResponse> {
+        let cassette = self.cassette.as_deref();
+        let recorded = cassette.map(|_| CassetteRequest::new(&request));
+
+        if let (Some(cassette), Some(request)) = (cassette, &recorded) {
+            if cassette.mode() == CassetteMode::Replay {
+                return cassette.play(request);
+            }
+        }
+
+        let response = self.http_client.execute(request).await?;
+        let status = response.status();
+        let headers = response.headers().clone();
+
+        // 解析に失敗した場合にも本文を残せるよう、先にすべて読み込む
+        let body = response.bytes().await?.to_vec();
+        let response = HttpResponse {
+            status,
+            headers,
+            body,
+        };
+
+        if let (Some(cassette), Some(request)) = (cassette, recorded) {
+            cassette.record_interaction(request, &response);
+        }
+
+        Ok(response)
+    }
+
+    /// 指定したエンドポイントにルート検索を1回送信
+    async fn send_find_routers(
+        &self,
+        endpoint: &str,
+        params: &FindRouterParams,
+    ) -> Result<Option<RouterData>> {
         // 流動性変更があるかどうかでリクエスト方法を選択
-        let response = if params.liquidity_changes.is_some()
+        let request = if params.liquidity_changes.is_some()
             && !params.liquidity_changes.as_ref().unwrap().is_empty()
         {
-            self.post_router_with_liquidity_changes(&params).await?
+            self.post_router_with_liquidity_changes(endpoint, params)?
         } else {
-            self.get_router(&params).await?
+            self.get_router(endpoint, params)?
         };
+        let sent = SentRequest::new(&request);
+
+        // HTTPリクエストを実行
+        let response = self.execute(request).await?;
 
         // レスポンスを解析して返却
-        self.parse_router_response(response).await
+        let mut data = self.parse_router_response(response, sent, || RequestContext {
+            from: params.from.clone(),
+            target: params.target.clone(),
+            amount: params.amount,
+            by_amount_in: params.by_amount_in,
+            endpoint: endpoint.to_string(),
+        })?;
+
+        if let Some(ref mut data) = data {
+            // サーバーは入力量ベースフラグを省略することが多いため、リクエストの値で補完
+            data.by_amount_in.get_or_insert(params.by_amount_in);
+            self.verify_packages(data)?;
+        }
+
+        Ok(data)
+    }
+
+    /// レスポンスのパッケージを許可リストと照合
+    ///
+    /// ポリシーが[`PackagePolicy::Reject`]の場合は違反があればエラーを返し、
+    /// [`PackagePolicy::Flag`]の場合は違反を`RouterData.package_violations`に記録します。
+    fn verify_packages(&self, data: &mut RouterData) -> Result<()> {
+        let Some(ref verification) = self.package_verification else {
+            return Ok(());
+        };
+        let Some(ref packages) = data.packages else {
+            return Ok(());
+        };
+
+        let violations = verification
+            .allowlist
+            .verify(verification.network, packages);
+        if violations.is_empty() {
+            return Ok(());
+        }
+
+        match verification.policy {
+            PackagePolicy::Reject => Err(AggregatorError::PackageNotAllowed(violations)),
+            PackagePolicy::Flag => {
+                data.package_violations = violations;
+                Ok(())
+            }
+        }
+    }
+
+    /// 指定したエンドポイントにルート検索を送信し、結果を健全性の統計に記録
+    ///
+    /// 再試行可能なエラーのみをエンドポイントの失敗として扱います。
+    /// ルートなしなどのエラーはサーバーが正常に応答した結果とみなします。
+    async fn request_endpoint(
+        &self,
+        endpoint: &Endpoint,
+        params: &FindRouterParams,
+        priority: Priority,
+    ) -> Result<Option<RouterData>> {
+        let circuit_open = |retry_in| AggregatorError::CircuitOpen {
+            endpoint: endpoint.url.clone(),
+            retry_in,
+        };
+
+        // 回路が開いている場合は制限を待たずに失敗させる
+        if let Some(retry_in) = endpoint.blocked_for() {
+            return Err(circuit_open(retry_in));
+        }
+
+        // 制限を待つ時間はレイテンシに含めない
+        let _limit = match self.limiter {
+            Some(ref limiter) => Some(limiter.acquire(priority).await),
+            None => None,
+        };
+
+        // 試験的な送信は制限を待った後に確保し、送信がキャンセルされた場合はドロップ時に返却する
+        let permit = endpoint.try_acquire().map_err(circuit_open)?;
+
+        let started = Instant::now();
+        let result = self.send_find_routers(&endpoint.url, params).await;
+
+        match &result {
+            Err(e) if e.is_retryable() => {
+                let timed_out = matches!(e, AggregatorError::RequestError(e) if e.is_timeout());
+                permit.record_failure(&e.to_string(), timed_out)
+            }
+            Ok(_) => permit.record_success(started.elapsed()),
+            Err(e) if is_server_answer(e) => permit.record_success(started.elapsed()),
+            // 送信前のエラーやレスポンスの解析エラーではエンドポイントの健全性を判断できないため、
+            // 結果を記録せずに試験的な送信を返却する
+            Err(_) => drop(permit),
+        }
+
+        result
+    }
+
+    /// 健全なエンドポイントから順に送信し、再試行可能なエラーの場合は次のエンドポイントへ切り替える
+    async fn find_routers_failover(
+        &self,
+        params: &FindRouterParams,
+        priority: Priority,
+    ) -> Result<Option<RouterData>> {
+        let mut last_error = None;
+
+        for endpoint in self.endpoints.ordered() {
+            match self.request_endpoint(endpoint, params, priority).await {
+                Err(e) if should_try_next(&e) => last_error = Some(e),
+                result => return result,
+            }
+        }
+
+        // エンドポイントは必ず1つ以上存在するため、ここに到達した場合はエラーが記録されている
+        Err(last_error.expect("エンドポイントが設定されていません"))
+    }
+
+    /// ヘッジリクエストによるルート検索
+    ///
+    /// 最優先のエンドポイントに送信し、`delay`以内に応答がなければ次のエンドポイントにも送信します。
+    /// 先に返ってきた成功応答（または再試行しても結果が変わらないエラー）を採用し、
+    /// 残りのリクエストはキャンセルします。エンドポイントが1つの場合は同じエンドポイントに再送します。
+    async fn find_routers_hedged(
+        &self,
+        params: &FindRouterParams,
+        delay: Duration,
+        priority: Priority,
+    ) -> Result<Option<RouterData>> {
+        let ordered = self.endpoints.ordered();
+        let first = ordered[0];
+        let second = ordered.get(1).copied().unwrap_or(first);
+
+        let primary = self.request_endpoint(first, params, priority);
+        tokio::pin!(primary);
+
+        // 待機時間内に最初のリクエストが完了した場合
+        let primary_failed = tokio::select! {
+            result = &mut primary => match result {
+                Err(e) if should_try_next(&e) => true,
+                result => return result,
+            },
+            _ = tokio::time::sleep(delay) => false,
+        };
+
+        let secondary = self.request_endpoint(second, params, priority);
+        if primary_failed {
+            return secondary.await;
+        }
+        tokio::pin!(secondary);
+
+        // 両方のリクエストを並行して待機し、先に確定した結果を採用
+        tokio::select! {
+            result = &mut primary => match result {
+                Err(e) if should_try_next(&e) => secondary.await,
+                result => result,
+            },
+            result = &mut secondary => match result {
+                Err(e) if should_try_next(&e) => primary.await,
+                result => result,
+            },
+        }
+    }
+
+    /// リトライを行わずにルート検索を1回実行
+    async fn find_routers_once(
+        &self,
+        params: &FindRouterParams,
+        priority: Priority,
+    ) -> Result<Option<RouterData>> {
+        match self.strategy {
+            RequestStrategy::Failover => self.find_routers_failover(params, priority).await,
+            RequestStrategy::Hedged { delay } => {
+                self.find_routers_hedged(params, delay, priority).await
+            }
+        }
+    }
+}
+
+/// TLSバックエンドの選択
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TlsBackend {
+    /// reqwestのデフォルトバックエンド
+    #[default]
+    Default,
+    /// ネイティブTLS（`native-tls`フィーチャーが必要）
+    #[cfg(feature = "native-tls")]
+    NativeTls,
+    /// Rustls（`rustls-tls`フィーチャーが必要）
+    #[cfg(feature = "rustls-tls")]
+    Rustls,
+}
+
+/// ビルド時に報告する設定エラー
+///
+/// メッセージはビルダーに設定したロケールで[`build`](AggregatorClientBuilder::build)時に作成します。
+#[derive(Debug)]
+enum BuilderError {
+    /// 不正なヘッダー名または値
+    InvalidHeader(String),
+    /// 不正なAPIキー
+    InvalidApiKey,
+    /// 範囲外のタイムアウト率のしきい値
+    InvalidTimeoutRate,
+    /// 不正なレート制限
+    InvalidRateLimit(RateLimit),
+    /// 0に設定された同時実行数の上限
+    ZeroMaxInFlight,
+}
+
+impl BuilderError {
+    fn message(&self, locale: Locale) -> String {
+        match self {
+            Self::InvalidHeader(name) => {
+                tr!(locale => "不正なヘッダーです: {}", "invalid header: {}", name)
+            }
+            Self::InvalidApiKey => tr!(locale => "不正なAPIキーです", "invalid API key"),
+            Self::InvalidTimeoutRate => tr!(
+                locale =>
+                "タイムアウト率のしきい値は0以上1以下で指定してください",
+                "timeout rate threshold must be between 0 and 1"
+            ),
+            Self::InvalidRateLimit(rate_limit) => rate_limit.violation(locale).unwrap_or_default(),
+            Self::ZeroMaxInFlight => tr!(
+                locale =>
+                "同時に送信するリクエスト数の上限は1以上で指定してください",
+                "max in-flight requests must be at least 1"
+            ),
+        }
+    }
+}
+
+/// [`AggregatorClient`]のビルダー
+///
+/// エンドポイントを複数指定した場合は、設定順に優先度が高いものとして扱います。
+///
+/// `http_client`で既存のクライアントを注入した場合も、APIキー・ユーザーエージェント・
+/// デフォルトヘッダーはリクエストごとに付与されます。タイムアウト・プロキシ・TLSは
+/// クライアント単位の設定のため注入したクライアント側で行う必要があり、
+/// 両方を指定した場合、[`build`](Self::build)は入力パラメータエラーを返します。
+#[derive(Debug, Default)]
+pub struct AggregatorClientBuilder {
+    /// APIエンドポイントの一覧
+    endpoints: Vec<String>,
+    /// 複数エンドポイントへのリクエスト方式
+    strategy: RequestStrategy,
+    /// 不健全と判定するまでの連続失敗回数
+    unhealthy_threshold: Option<u32>,
+    /// 不健全なエンドポイントを再び試すまでの時間
+    recovery_interval: Option<Duration>,
+    /// エンドポイントごとのサーキットブレーカー
+    circuit_breaker: Option<CircuitBreaker>,
+    /// 注入されたHTTPクライアント
+    http_client: Option<HttpClient>,
+    /// 接続タイムアウト
+    connect_timeout: Option<Duration>,
+    /// 読み取りタイムアウト
+    read_timeout: Option<Duration>,
+    /// リクエスト全体のタイムアウト
+    timeout: Option<Duration>,
+    /// デフォルトヘッダー
+    default_headers: HeaderMap,
+    /// ユーザーエージェント
+    user_agent: Option<String>,
+    /// プロキシ設定
+    proxies: Vec<Proxy>,
+    /// システムプロキシを無効化するかどうか
+    no_proxy: bool,
+    /// TLSバックエンド
+    tls_backend: TlsBackend,
+    /// リトライポリシー
+    retry_policy: Option<RetryPolicy>,
+    /// パッケージの許可リストによる検証
+    package_verification: Option<PackageVerification>,
+    /// パラメータの検証で使用する上限
+    param_limits: Option<ParamLimits>,
+    /// リクエストに付与するSDKバージョン
+    sdk_version: Option<String>,
+    /// ルート検索APIのパス
+    api_path: Option<String>,
+    /// メッセージの言語
+    locale: Option<Locale>,
+    /// 通信を記録・再生するカセット
+    cassette: Option<Cassette>,
+    /// レート制限
+    rate_limit: Option<RateLimit>,
+    /// 同時に送信するリクエスト数の上限
+    max_in_flight: Option<usize>,
+    /// ビルド時に報告する設定エラー
+    errors: Vec<BuilderError>,
+}
+
+impl AggregatorClientBuilder {
+    /// APIエンドポイントを設定（設定済みのエンドポイントは置き換えられます）
+    pub fn endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.endpoints = vec![endpoint.into()];
+        self
+    }
+
+    /// 優先度順のAPIエンドポイント一覧を設定（設定済みのエンドポイントは置き換えられます）
+    pub fn endpoints<I, S>(mut self, endpoints: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.endpoints = endpoints.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// フェイルオーバー先のAPIエンドポイントを追加
+    pub fn fallback_endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        if self.endpoints.is_empty() {
+            self.endpoints.push(DEFAULT_ENDPOINT.to_string());
+        }
+        self.endpoints.push(endpoint.into());
+        self
+    }
+
+    /// 複数エンドポイントへのリクエスト方式を設定
+    pub fn request_strategy(mut self, strategy: RequestStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// ヘッジリクエストを有効化
+    ///
+    /// `delay`経過しても応答がない場合に次のエンドポイントへ2つ目のリクエストを送信します。
+    pub fn hedged(self, delay: Duration) -> Self {
+        self.request_strategy(RequestStrategy::Hedged { delay })
+    }
+
+    /// 不健全と判定するまでの連続失敗回数を設定
+    ///
+    /// 不健全なエンドポイントは、健全なエンドポイントをすべて試した後に使用されます。
+    pub fn unhealthy_threshold(mut self, threshold: u32) -> Self {
+        self.unhealthy_threshold = Some(threshold);
+        self
+    }
+
+    /// 不健全なエンドポイントを再び試すまでの、最後の失敗からの経過時間を設定
+    ///
+    /// デフォルトは[`DEFAULT_RECOVERY_INTERVAL`]です。経過後は設定順の位置で試され、
+    /// 成功すれば健全に戻ります。
+    pub fn recovery_interval(mut self, recovery_interval: Duration) -> Self {
+        self.recovery_interval = Some(recovery_interval);
+        self
+    }
+
+    /// エンドポイントごとのサーキットブレーカーを設定
+    ///
+    /// 回路が開いているエンドポイントには送信せず、フェイルオーバーやヘッジリクエストでは次のエンドポイントを使用します。
+    /// すべてのエンドポイントの回路が開いている場合は[`AggregatorError::CircuitOpen`]を返します。
+    pub fn circuit_breaker(mut self, circuit_breaker: CircuitBreaker) -> Self {
+        if circuit_breaker
+            .timeout_rate_threshold
+            .is_some_and(|rate| !(0.0..=1.0).contains(&rate))
+        {
+            self.errors.push(BuilderError::InvalidTimeoutRate);
+        }
+        self.circuit_breaker = Some(circuit_breaker);
+        self
+    }
+
+    /// 事前に設定済みの`reqwest::Client`を注入
+    ///
+    /// 複数のサービス間でコネクションプールを共有する場合に使用します。
+    pub fn http_client(mut self, http_client: HttpClient) -> Self {
+        self.http_client = Some(http_client);
+        self
+    }
+
+    /// 接続タイムアウトを設定
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// 読み取りタイムアウトを設定
+    pub fn read_timeout(mut self, timeout: Duration) -> Self {
+        self.read_timeout = Some(timeout);
+        self
+    }
+
+    /// リクエスト全体（接続からレスポンス本文の受信まで）のタイムアウトを設定
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// デフォルトヘッダーを追加
+    ///
+    /// ヘッダー名または値が不正な場合は[`build`](Self::build)でエラーになります。
+    pub fn default_header(mut self, name: &str, value: &str) -> Self {
+        match (
+            HeaderName::from_bytes(name.as_bytes()),
+            HeaderValue::from_str(value),
+        ) {
+            (Ok(name), Ok(value)) => {
+                self.default_headers.insert(name, value);
+            }
+            _ => self
+                .errors
+                .push(BuilderError::InvalidHeader(name.to_string())),
+        }
+        self
+    }
+
+    /// デフォルトヘッダーをまとめて追加
+    pub fn default_headers(mut self, headers: HeaderMap) -> Self {
+        self.default_headers.extend(headers);
+        self
+    }
+
+    /// APIキーを設定
+    ///
+    /// キーは[`API_KEY_HEADER`]ヘッダーとして送信され、ログ出力時にはマスクされます。
+    pub fn api_key(mut self, api_key: &str) -> Self {
+        match HeaderValue::from_str(api_key) {
+            Ok(mut value) => {
+                value.set_sensitive(true);
+                self.default_headers
+                    .insert(HeaderName::from_static(API_KEY_HEADER), value);
+            }
+            Err(_) => self.errors.push(BuilderError::InvalidApiKey),
+        }
+        self
+    }
+
+    /// ユーザーエージェントを設定
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// プロキシを追加
+    pub fn proxy(mut self, proxy: Proxy) -> Self {
+        self.proxies.push(proxy);
+        self
+    }
+
+    /// システムプロキシを含むすべてのプロキシを無効化
+    pub fn no_proxy(mut self) -> Self {
+        self.no_proxy = true;
+        self
+    }
+
+    /// TLSバックエンドを選択
+    pub fn tls_backend(mut self, tls_backend: TlsBackend) -> Self {
+        self.tls_backend = tls_backend;
+        self
+    }
+
+    /// リトライポリシーを設定（デフォルトはリトライなし）
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(retry_policy);
+        self
+    }
+
+    /// パッケージの許可リストによる検証を有効化
+    ///
+    /// # 引数
+    ///
+    /// * `allowlist` - 許可するパッケージIDの一覧
+    /// * `network` - 接続先のネットワーク
+    /// * `policy` - 許可されていないパッケージを検出した場合の扱い
+    pub fn package_allowlist(
+        mut self,
+        allowlist: PackageAllowlist,
+        network: Network,
+        policy: PackagePolicy,
+    ) -> Self {
+        self.package_verification = Some(PackageVerification {
+            allowlist,
+            network,
+            policy,
+        });
+        self
+    }
+
+    /// 送信前のパラメータの検証で使用する深さ・分割数の上限を設定
+    ///
+    /// デフォルトは上限なしです。
+    pub fn param_limits(mut self, limits: ParamLimits) -> Self {
+        self.param_limits = Some(limits);
+        self
+    }
+
+    /// リクエストに付与するSDKバージョン（`v`パラメータ）を設定
+    ///
+    /// デフォルトは[`DEFAULT_SDK_VERSION`]です。
+    pub fn sdk_version(mut self, sdk_version: impl Into<String>) -> Self {
+        self.sdk_version = Some(sdk_version.into());
+        self
+    }
+
+    /// ルート検索APIのパスを設定
+    ///
+    /// エンドポイントからの相対パスで、デフォルトは[`DEFAULT_API_PATH`]です。
+    /// `router_v3`などのエンドポイントと組み合わせて、新しいバージョンのAPIを利用できます。
+    pub fn api_path(mut self, api_path: impl Into<String>) -> Self {
+        self.api_path = Some(api_path.into());
+        self
+    }
+
+    /// メッセージの言語を設定（デフォルトはプロセス全体のロケール）
+    pub fn locale(mut self, locale: Locale) -> Self {
+        self.locale = Some(locale);
+        self
+    }
+
+    /// 通信を記録・再生するカセットを設定
+    ///
+    /// 再生モードではネットワークに接続せず、記録済みのレスポンスを返します。
+    /// 記録モードでは、[`Cassette::save`]を呼び出すか、クライアント（とそのクローン）をすべてドロップすると
+    /// ファイルに書き込みます。
+    pub fn cassette(mut self, cassette: Cassette) -> Self {
+        self.cassette = Some(cassette);
+        self
+    }
+
+    /// トークンバケットによるレート制限を設定
+    ///
+    /// 再試行やヘッジリクエストを含む、すべてのHTTPリクエストに適用されます。
+    /// 制限はクローンしたクライアント間で共有されます。
+    pub fn rate_limit(mut self, rate_limit: RateLimit) -> Self {
+        if rate_limit.violation(Locale::default()).is_some() {
+            self.errors.push(BuilderError::InvalidRateLimit(rate_limit));
+        }
+        self.rate_limit = Some(rate_limit);
+        self
+    }
+
+    /// 同時に送信するリクエスト数の上限を設定
+    ///
+    /// 上限はクローンしたクライアント間で共有されます。
+    pub fn max_in_flight(mut self, max_in_flight: usize) -> Self {
+        if max_in_flight == 0 {
+            self.errors.push(BuilderError::ZeroMaxInFlight);
+        }
+        self.max_in_flight = Some(max_in_flight);
+        self
+    }
+
+    /// クライアント単位でしか設定できないHTTPクライアントの設定が指定されているかどうか
+    ///
+    /// ヘッダーとユーザーエージェントはリクエストごとに付与するため含みません。
+    fn has_client_options(&self) -> bool {
+        self.connect_timeout.is_some()
+            || self.read_timeout.is_some()
+            || self.timeout.is_some()
+            || !self.proxies.is_empty()
+            || self.no_proxy
+            || self.tls_backend != TlsBackend::Default
+    }
+
+    /// クライアントを構築
+    ///
+    /// # 戻り値
+    ///
+    /// 成功した場合はAggregatorClient、設定が不正な場合はエラーを返します。
+    pub fn build(mut self) -> Result<AggregatorClient> {
+        let locale = self.locale.unwrap_or_else(global_locale);
+        if !self.errors.is_empty() {
+            let messages: Vec<String> = self
+                .errors
+                .iter()
+                .map(|error| error.message(locale))
+                .collect();
+            return Err(AggregatorError::InputError(messages.join(", ")));
+        }
+
+        let mut endpoints = std::mem::take(&mut self.endpoints);
+        if endpoints.is_empty() {
+            endpoints.push(DEFAULT_ENDPOINT.to_string());
+        }
+
+        let invalid: Vec<String> = endpoints
+            .iter()
+            .filter(|endpoint| Url::parse(endpoint).map_or(true, |url| url.cannot_be_a_base()))
+            .map(|endpoint| {
+                tr!(
+                    locale =>
+                    "不正なエンドポイントです: {}",
+                    "invalid endpoint: {}",
+                    endpoint
+                )
+            })
+            .collect();
+        if !invalid.is_empty() {
+            return Err(AggregatorError::InputError(invalid.join(", ")));
+        }
+
+        let http_client = match self.http_client {
+            Some(_) if self.has_client_options() => {
+                return Err(AggregatorError::InputError(
+                    tr!(locale => "HTTPクライアントを注入する場合、タイムアウト・プロキシ・TLSは注入するクライアント側で設定してください", "when injecting an HTTP client, configure timeouts, proxies and TLS on the injected client"),
+                ));
+            }
+            Some(ref http_client) => http_client.clone(),
+            None => {
+                let mut builder = HttpClient::builder();
+
+                if let Some(timeout) = self.connect_timeout {
+                    builder = builder.connect_timeout(timeout);
+                }
+                if let Some(timeout) = self.read_timeout {
+                    builder = builder.read_timeout(timeout);
+                }
+                if let Some(timeout) = self.timeout {
+                    builder = builder.timeout(timeout);
+                }
+                if self.no_proxy {
+                    builder = builder.no_proxy();
+                }
+                for proxy in self.proxies {
+                    builder = builder.proxy(proxy);
+                }
+                match self.tls_backend {
+                    TlsBackend::Default => {}
+                    #[cfg(feature = "native-tls")]
+                    TlsBackend::NativeTls => builder = builder.use_native_tls(),
+                    #[cfg(feature = "rustls-tls")]
+                    TlsBackend::Rustls => builder = builder.use_rustls_tls(),
+                }
+
+                builder.build()?
+            }
+        };
+
+        // 注入したクライアントを共有する場合も、サービスごとのヘッダーはリクエストごとに付与する
+        let mut headers = self.default_headers;
+        if let Some(user_agent) = self.user_agent {
+            match HeaderValue::from_str(&user_agent) {
+                Ok(value) => {
+                    headers.insert(reqwest::header::USER_AGENT, value);
+                }
+                Err(_) => {
+                    return Err(AggregatorError::InputError(tr!(
+                        locale =>
+                        "不正なユーザーエージェントです: {}",
+                        "invalid user agent: {}",
+                        user_agent
+                    )))
+                }
+            }
+        }
+
+        Ok(AggregatorClient {
+            endpoints: Arc::new(EndpointSet::new(
+                endpoints,
+                self.unhealthy_threshold
+                    .unwrap_or(DEFAULT_UNHEALTHY_THRESHOLD),
+                self.recovery_interval.unwrap_or(DEFAULT_RECOVERY_INTERVAL),
+                self.circuit_breaker,
+            )),
+            strategy: self.strategy,
+            http_client,
+            headers,
+            retry_policy: self.retry_policy.unwrap_or_else(RetryPolicy::none),
+            package_verification: self.package_verification.map(Arc::new),
+            param_limits: self.param_limits.unwrap_or_default(),
+            sdk_version: self
+                .sdk_version
+                .unwrap_or_else(|| DEFAULT_SDK_VERSION.to_string()),
+            api_path: self
+                .api_path
+                .unwrap_or_else(|| DEFAULT_API_PATH.to_string()),
+            locale: self.locale,
+            cassette: self.cassette.map(Arc::new),
+            limiter: (self.rate_limit.is_some() || self.max_in_flight.is_some())
+                .then(|| Arc::new(Limiter::new(self.rate_limit, self.max_in_flight))),
+        })
+    }
+}
+
+#[async_trait]
+impl AggregatorClientTrait for AggregatorClient {
+    async fn find_routers(&self, params: FindRouterParams) -> Result<Option<RouterData>> {
+        self.find_routers_with_priority(params, Priority::Normal)
+            .await
     }
 }
@@ -0,0 +1,455 @@
+/**
+ * コインタイプの定義
+ *
+ * このモジュールはMoveの構造体タグ（`0x2::sui::SUI`など）を解析・正規化する型を定義します。
+ * アドレスは64桁の16進数に正規化されるため、短縮形式と完全形式を同一のコインとして比較できます。
+ */
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::error::AggregatorError;
+use crate::locale::tr;
+
+/// SUIのコインタイプ（短縮形式）
+pub const SUI_COIN_TYPE: &str = "0x2::sui::SUI";
+
+/// アドレスの16進数の桁数
+const ADDRESS_HEX_LENGTH: usize = 64;
+
+/// 型引数の入れ子の最大の深さ
+///
+/// Suiのプロトコル設定`max_type_argument_depth`（16）に合わせています。
+/// 深く入れ子になった入力で再帰が深くなりすぎることを防ぎます。
+pub const MAX_TYPE_ARGUMENT_DEPTH: usize = 16;
+
+/// Moveの型引数
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum TypeTag {
+    /// bool
+    Bool,
+    /// u8
+    U8,
+    /// u16
+    U16,
+    /// u32
+    U32,
+    /// u64
+    U64,
+    /// u128
+    U128,
+    /// u256
+    U256,
+    /// address
+    Address,
+    /// signer
+    Signer,
+    /// `vector<T>`
+    Vector(Box<TypeTag>),
+    /// 構造体
+    Struct(Box<CoinType>),
+}
+
+impl TypeTag {
+    fn write(&self, f: &mut fmt::Formatter<'_>, short: bool) -> fmt::Result {
+        match self {
+            Self::Bool => f.write_str("bool"),
+            Self::U8 => f.write_str("u8"),
+            Self::U16 => f.write_str("u16"),
+            Self::U32 => f.write_str("u32"),
+            Self::U64 => f.write_str("u64"),
+            Self::U128 => f.write_str("u128"),
+            Self::U256 => f.write_str("u256"),
+            Self::Address => f.write_str("address"),
+            Self::Signer => f.write_str("signer"),
+            Self::Vector(inner) => {
+                f.write_str("vector<")?;
+                inner.write(f, short)?;
+                f.write_str(">")
+            }
+            Self::Struct(coin) => coin.write(f, short),
+        }
+    }
+}
+
+impl fmt::Display for TypeTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write(f, false)
+    }
+}
+
+/// コインタイプ（Moveの構造体タグ）
+///
+/// アドレスは`0x`と64桁の小文字16進数に正規化して保持します。
+/// `Display`は完全形式で出力し、短縮形式は[`to_short_string`](Self::to_short_string)で取得できます。
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CoinType {
+    /// 正規化されたパッケージアドレス
+    address: String,
+    /// モジュール名
+    module: String,
+    /// 構造体名
+    name: String,
+    /// 型引数
+    type_params: Vec<TypeTag>,
+}
+
+impl CoinType {
+    /// SUIのコインタイプ
+    pub fn sui() -> Self {
+        Self {
+            address: normalize_address("0x2").expect("SUIのアドレスは常に有効"),
+            module: "sui".to_string(),
+            name: "SUI".to_string(),
+            type_params: Vec::new(),
+        }
+    }
+
+    /// 正規化されたパッケージアドレス（`0x`と64桁の16進数）
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    /// モジュール名
+    pub fn module(&self) -> &str {
+        &self.module
+    }
+
+    /// 構造体名
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// 型引数
+    pub fn type_params(&self) -> &[TypeTag] {
+        &self.type_params
+    }
+
+    /// SUIかどうか
+    pub fn is_sui(&self) -> bool {
+        *self == Self::sui()
+    }
+
+    /// アドレスを64桁に展開した完全形式の文字列
+    pub fn to_long_string(&self) -> String {
+        self.to_string()
+    }
+
+    /// アドレスの先頭の0を省略した短縮形式の文字列（例: `0x2::sui::SUI`）
+    pub fn to_short_string(&self) -> String {
+        struct Short<'a>(&'a CoinType);
+
+        impl fmt::Display for Short<'_> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                self.0.write(f, true)
+            }
+        }
+
+        Short(self).to_string()
+    }
+
+    fn write(&self, f: &mut fmt::Formatter<'_>, short: bool) -> fmt::Result {
+        if short {
+            write!(f, "{}", short_address(&self.address))?;
+        } else {
+            f.write_str(&self.address)?;
+        }
+        write!(f, "::{}::{}", self.module, self.name)?;
+
+        if !self.type_params.is_empty() {
+            f.write_str("<")?;
+            for (i, param) in self.type_params.iter().enumerate() {
+                if i > 0 {
+                    f.write_str(", ")?;
+                }
+                param.write(f, short)?;
+            }
+            f.write_str(">")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for CoinType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write(f, false)
+    }
+}
+
+impl FromStr for CoinType {
+    type Err = AggregatorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser::new(s);
+        let coin = parser
+            .parse_struct()
+            .and_then(|coin| parser.finish().map(|_| coin))
+            .map_err(|reason| {
+                AggregatorError::InputError(tr!(
+                    "不正なコインタイプです ({}): {}",
+                    "invalid coin type ({}): {}",
+                    reason,
+                    s
+                ))
+            })?;
+        Ok(coin)
+    }
+}
+
+impl TryFrom<&str> for CoinType {
+    type Error = AggregatorError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl TryFrom<String> for CoinType {
+    type Error = AggregatorError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl Serialize for CoinType {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for CoinType {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// アドレスを`0x`と64桁の小文字16進数に正規化
+///
+/// `0x`の有無は問わず、桁数が足りない場合は先頭を0で埋めます。
+pub fn normalize_address(address: &str) -> Option<String> {
+    let hex = address
+        .strip_prefix("0x")
+        .or_else(|| address.strip_prefix("0X"))
+        .unwrap_or(address);
+
+    if hex.is_empty()
+        || hex.len() > ADDRESS_HEX_LENGTH
+        || !hex.chars().all(|c| c.is_ascii_hexdigit())
+    {
+        return None;
+    }
+
+    Some(format!(
+        "0x{:0>width$}",
+        hex.to_ascii_lowercase(),
+        width = ADDRESS_HEX_LENGTH
+    ))
+}
+
+/// 正規化済みアドレスの先頭の0を省略
+fn short_address(address: &str) -> String {
+    let hex = address.trim_start_matches("0x").trim_start_matches('0');
+    if hex.is_empty() {
+        "0x0".to_string()
+    } else {
+        format!("0x{}", hex)
+    }
+}
+
+/// 構造体タグの再帰下降パーサー
+struct Parser<'a> {
+    input: &'a str,
+    /// 現在の位置（バイト単位）
+    pos: usize,
+    /// 型引数の入れ子の深さ
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(input: &'a str) -> Self {
+        Self {
+            input,
+            pos: 0,
+            depth: 0,
+        }
+    }
+
+    /// 現在の位置（1から始まる文字数）
+    fn column(&self) -> usize {
+        self.input[..self.pos].chars().count() + 1
+    }
+
+    /// 型引数の内側に入る
+    fn enter(&mut self) -> Result<(), String> {
+        self.depth += 1;
+        if self.depth > MAX_TYPE_ARGUMENT_DEPTH {
+            return Err(tr!(
+                "{}文字目で型引数の入れ子が上限（{}）を超えています",
+                "type arguments are nested deeper than {1} at character {0}",
+                self.column(),
+                MAX_TYPE_ARGUMENT_DEPTH
+            ));
+        }
+        Ok(())
+    }
+
+    /// 型引数の内側から出る
+    fn leave(&mut self) {
+        self.depth -= 1;
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        self.skip_whitespace();
+        if self.rest().starts_with(token) {
+            self.pos += token.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: &str) -> Result<(), String> {
+        if self.eat(token) {
+            Ok(())
+        } else {
+            Err(tr!(
+                "{}文字目に`{}`が必要です",
+                "expected `{1}` at character {0}",
+                self.column(),
+                token
+            ))
+        }
+    }
+
+    fn identifier(&mut self) -> Result<&'a str, String> {
+        self.skip_whitespace();
+        let rest = self.rest();
+        let len = rest
+            .char_indices()
+            .find(|&(i, c)| !(c == '_' || c.is_ascii_alphabetic() || (i > 0 && c.is_ascii_digit())))
+            .map(|(i, _)| i)
+            .unwrap_or(rest.len());
+
+        if len == 0 {
+            return Err(tr!(
+                "{}文字目に識別子が必要です",
+                "expected an identifier at character {}",
+                self.column()
+            ));
+        }
+        self.pos += len;
+        Ok(&rest[..len])
+    }
+
+    fn parse_struct(&mut self) -> Result<CoinType, String> {
+        let address = self.identifier_or_address()?;
+        let address = normalize_address(address)
+            .ok_or_else(|| tr!("不正なアドレスです: {}", "invalid address: {}", address))?;
+        self.expect("::")?;
+        let module = self.identifier()?.to_string();
+        self.expect("::")?;
+        let name = self.identifier()?.to_string();
+
+        let mut type_params = Vec::new();
+        if self.eat("<") {
+            self.enter()?;
+            loop {
+                type_params.push(self.parse_type_tag()?);
+                if self.eat(">") {
+                    break;
+                }
+                self.expect(",")?;
+            }
+            self.leave();
+        }
+
+        Ok(CoinType {
+            address,
+            module,
+            name,
+            type_params,
+        })
+    }
+
+    fn identifier_or_address(&mut self) -> Result<&'a str, String> {
+        self.skip_whitespace();
+        let rest = self.rest();
+        let len = rest
+            .find(|c: char| !c.is_ascii_alphanumeric())
+            .unwrap_or(rest.len());
+
+        if len == 0 {
+            return Err(tr!(
+                "{}文字目にアドレスが必要です",
+                "expected an address at character {}",
+                self.column()
+            ));
+        }
+        self.pos += len;
+        Ok(&rest[..len])
+    }
+
+    fn parse_type_tag(&mut self) -> Result<TypeTag, String> {
+        self.skip_whitespace();
+        let start = self.pos;
+        let word = self.identifier_or_address()?;
+
+        // `::`が続く場合は構造体として解析し直す
+        if self.rest().trim_start().starts_with("::") {
+            self.pos = start;
+            return Ok(TypeTag::Struct(Box::new(self.parse_struct()?)));
+        }
+
+        Ok(match word {
+            "bool" => TypeTag::Bool,
+            "u8" => TypeTag::U8,
+            "u16" => TypeTag::U16,
+            "u32" => TypeTag::U32,
+            "u64" => TypeTag::U64,
+            "u128" => TypeTag::U128,
+            "u256" => TypeTag::U256,
+            "address" => TypeTag::Address,
+            "signer" => TypeTag::Signer,
+            "vector" => {
+                self.expect("<")?;
+                self.enter()?;
+                let inner = self.parse_type_tag()?;
+                self.expect(">")?;
+                self.leave();
+                TypeTag::Vector(Box::new(inner))
+            }
+            other => return Err(tr!("不明な型です: {}", "unknown type: {}", other)),
+        })
+    }
+
+    fn finish(&mut self) -> Result<(), String> {
+        self.skip_whitespace();
+        if self.rest().is_empty() {
+            Ok(())
+        } else {
+            Err(tr!(
+                "{}文字目以降に余分な文字があります",
+                "unexpected trailing characters from character {}",
+                self.column()
+            ))
+        }
+    }
+}
@@ -0,0 +1,354 @@
+/**
+ * 取引量ごとの見積もり曲線
+ *
+ * このモジュールは複数の取引量でルート検索を実行し、取引量に対する出力量・価格・価格インパクトの
+ * 変化（流動性の深さ）を表す見積もり曲線を作成する機能を定義します。
+ * 標本点の間の補間と、価格インパクトが上限以下に収まる最大の取引量の二分探索もできます。
+ */
+use primitive_types::U256;
+
+use crate::amount::mul_div;
+use crate::client::{AggregatorClient, AggregatorClientTrait};
+use crate::coin::CoinType;
+use crate::error::{AggregatorError, AggregatorServerErrorCode, Result};
+use crate::models::{FindRouterParams, RouterData};
+use crate::quote::{PriceImpact, BPS_DENOMINATOR};
+use crate::ratio::Ratio;
+
+/// `min`から`max`まで対数で等間隔な`count`個の金額を作成
+///
+/// 最初と最後の金額はそれぞれ`min`と`max`に一致します。
+/// 丸めにより重複した金額は1つにまとめるため、`count`より少なくなる場合があります。
+/// `min`が0の場合は1から開始します。
+///
+/// ```
+/// use cetus_aggregator_rust::curve::log_spaced_amounts;
+/// use primitive_types::U256;
+///
+/// let amounts = log_spaced_amounts(U256::from(1_000u64), U256::from(1_000_000u64), 4);
+/// assert_eq!(
+///     amounts,
+///     vec![
+///         U256::from(1_000u64),
+///         U256::from(10_000u64),
+///         U256::from(100_000u64),
+///         U256::from(1_000_000u64)
+///     ]
+/// );
+/// ```
+pub fn log_spaced_amounts(min: U256, max: U256, count: usize) -> Vec<U256> {
+    let min = min.max(U256::one());
+    if count == 0 || max < min {
+        return Vec::new();
+    }
+    if count == 1 || max == min {
+        return vec![max];
+    }
+
+    let log_min = to_f64(min).ln();
+    let log_max = to_f64(max).ln();
+    let step = (log_max - log_min) / (count - 1) as f64;
+
+    let mut amounts: Vec<U256> = (0..count)
+        .map(|i| match i {
+            0 => min,
+            i if i == count - 1 => max,
+            i => from_f64((log_min + step * i as f64).exp()).clamp(min, max),
+        })
+        .collect();
+    amounts.dedup();
+    amounts
+}
+
+/// 見積もり曲線の1点
+#[derive(Debug, Clone)]
+pub struct CurvePoint {
+    /// 入力量
+    pub amount_in: U256,
+    /// 出力量
+    pub amount_out: U256,
+    /// 入力の最小単位あたりの出力量（出力量/入力量）
+    pub output_per_unit: Ratio,
+    /// 直前の点からの限界価格（増えた出力量/増えた入力量）
+    ///
+    /// 最初の点、または出力量が増えていない場合は`None`です。
+    pub marginal_price: Option<Ratio>,
+    /// 見積もり全体の価格インパクト（計算できない場合は`None`）
+    pub price_impact: Option<PriceImpact>,
+    /// 直前の点と使用するプールの組み合わせが異なる場合は真（最初の点は偽）
+    pub route_changed: bool,
+    /// ルート検索の結果
+    pub data: RouterData,
+}
+
+impl CurvePoint {
+    /// ルート検索の結果と直前の点から作成
+    ///
+    /// 入力量が0の場合は`None`を返します。
+    fn new(data: RouterData, previous: Option<&CurvePoint>) -> Option<Self> {
+        let output_per_unit = data.execution_price()?;
+        let marginal_price = previous.and_then(|previous| {
+            let added_out = data.amount_out.checked_sub(previous.amount_out)?;
+            let added_in = data.amount_in.checked_sub(previous.amount_in)?;
+            Ratio::new(added_out, added_in).filter(|price| !price.is_zero())
+        });
+        let route_changed = previous
+            .is_some_and(|previous| route_signature(&previous.data) != route_signature(&data));
+
+        Some(Self {
+            amount_in: data.amount_in,
+            amount_out: data.amount_out,
+            output_per_unit,
+            marginal_price,
+            price_impact: data.price_impact().ok().flatten(),
+            route_changed,
+            data,
+        })
+    }
+
+    /// 不利な方向の価格インパクト（ベーシスポイント、切り上げ）
+    ///
+    /// 約定価格が有利な場合は0、価格インパクトを計算できない場合は`None`を返します。
+    pub fn impact_bps(&self) -> Option<U256> {
+        self.price_impact.as_ref().map(|impact| {
+            if impact.favorable {
+                U256::zero()
+            } else {
+                impact.bps()
+            }
+        })
+    }
+
+    /// 価格インパクトが`max_impact_bps`以下かどうか
+    ///
+    /// 価格インパクトを計算できない場合は上限を超えたものとして扱います。
+    pub fn within_impact(&self, max_impact_bps: u32) -> bool {
+        self.impact_bps()
+            .is_some_and(|bps| bps <= U256::from(max_impact_bps))
+    }
+}
+
+/// 取引量ごとの見積もり曲線
+///
+/// 点は入力量の昇順に並びます。ルートが見つからなかった入力量（ルートなし・流動性不足のエラーを含む）は
+/// `no_route`に含まれます。
+#[derive(Debug, Clone)]
+pub struct QuoteCurve {
+    /// 標本の作成に使用したパラメータ（金額以外）
+    pub params: FindRouterParams,
+    /// 入力量の昇順に並んだ点
+    pub points: Vec<CurvePoint>,
+    /// ルートが見つからなかった入力量
+    pub no_route: Vec<U256>,
+}
+
+impl QuoteCurve {
+    /// 使用するプールの組み合わせが直前の点から変わった点
+    pub fn route_changes(&self) -> impl Iterator<Item = &CurvePoint> {
+        self.points.iter().filter(|point| point.route_changed)
+    }
+
+    /// 入力量に対する出力量を標本点の間の線形補間で推定
+    ///
+    /// 最初の点より小さい入力量は原点（0, 0）との間で補間します。
+    /// 最後の点より大きい入力量は推定できないため`None`を返します。
+    pub fn interpolate(&self, amount_in: U256) -> Option<U256> {
+        let index = self
+            .points
+            .iter()
+            .position(|point| point.amount_in >= amount_in)?;
+        let upper = &self.points[index];
+        if upper.amount_in == amount_in {
+            return Some(upper.amount_out);
+        }
+        let (lower_in, lower_out) = match index {
+            0 => (U256::zero(), U256::zero()),
+            i => (self.points[i - 1].amount_in, self.points[i - 1].amount_out),
+        };
+
+        let offset = amount_in - lower_in;
+        let width = upper.amount_in - lower_in;
+        if upper.amount_out >= lower_out {
+            lower_out.checked_add(mul_div(upper.amount_out - lower_out, offset, width)?)
+        } else {
+            lower_out.checked_sub(mul_div(lower_out - upper.amount_out, offset, width)?)
+        }
+    }
+
+    /// 価格インパクトが`max_impact_bps`以下の点のうち、入力量が最大の点
+    ///
+    /// 価格インパクトは取引量に対して単調に増加するとみなし、最初に上限を超えた点より前から探します。
+    pub fn max_within_impact(&self, max_impact_bps: u32) -> Option<&CurvePoint> {
+        self.points
+            .iter()
+            .take_while(|point| point.within_impact(max_impact_bps))
+            .last()
+    }
+}
+
+impl AggregatorClient {
+    /// 複数の入力量でルート検索を実行して見積もり曲線を作成
+    ///
+    /// 標本の金額には[`log_spaced_amounts`]で作成した対数で等間隔な金額を指定すると、
+    /// 少額から大口まで少ない点数で流動性の深さを確認できます。
+    /// ルート検索は[`find_routers_batch`](Self::find_routers_batch)で並行して実行します。
+    ///
+    /// # 戻り値
+    ///
+    /// 見積もり曲線。ルートなし・流動性不足のエラーは`no_route`に含め、
+    /// それ以外のエラーでルート検索に失敗した場合は、入力量が最も小さいもののエラーを返します。
+    pub async fn quote_curve(
+        &self,
+        from: CoinType,
+        target: CoinType,
+        amounts: impl IntoIterator<Item = U256>,
+    ) -> Result<QuoteCurve> {
+        let params = FindRouterParams {
+            from,
+            target,
+            by_amount_in: true,
+            ..Default::default()
+        };
+        self.quote_curve_with(params, amounts).await
+    }
+
+    /// パラメータを指定して見積もり曲線を作成
+    ///
+    /// `params`の金額は標本の金額で置き換え、入力量ベースで見積もります。
+    /// 深さ・分割方法・プロバイダーなどはすべての標本で共通です。
+    pub async fn quote_curve_with(
+        &self,
+        mut params: FindRouterParams,
+        amounts: impl IntoIterator<Item = U256>,
+    ) -> Result<QuoteCurve> {
+        params.by_amount_in = true;
+        let mut amounts: Vec<U256> = amounts.into_iter().filter(|a| !a.is_zero()).collect();
+        amounts.sort();
+        amounts.dedup();
+
+        let batch = amounts
+            .iter()
+            .map(|amount| FindRouterParams {
+                amount: *amount,
+                ..params.clone()
+            })
+            .collect();
+        let items = self.find_routers_batch(batch).await;
+
+        let mut points: Vec<CurvePoint> = Vec::with_capacity(items.len());
+        let mut no_route = Vec::new();
+        for item in items {
+            let data = match item.result {
+                Ok(Some(data)) => Some(data),
+                Ok(None) => None,
+                Err(e) if is_no_route(&e) => None,
+                Err(e) => return Err(e),
+            };
+            match data.and_then(|data| CurvePoint::new(data, points.last())) {
+                Some(point) => points.push(point),
+                None => no_route.push(item.params.amount),
+            }
+        }
+
+        Ok(QuoteCurve {
+            params,
+            points,
+            no_route,
+        })
+    }
+
+    /// 価格インパクトが`max_impact_bps`以下に収まる最大の入力量を二分探索
+    ///
+    /// 見積もり曲線の点のうち上限以下で最大の点と、その次の上限を超えた点の間を探索します。
+    /// 最初の点が上限を超えている場合は0との間を探索し、すべての点が上限以下の場合は最後の点を返します。
+    /// 価格インパクトは取引量に対して単調に増加するとみなします。
+    ///
+    /// # 引数
+    ///
+    /// * `curve` - 見積もり曲線
+    /// * `max_impact_bps` - 許容する価格インパクト（ベーシスポイント、10000以下）
+    /// * `iterations` - 探索で実行するルート検索の最大回数
+    ///
+    /// # 戻り値
+    ///
+    /// 上限以下に収まる入力量が最大の点。上限以下に収まる点が見つからない場合は`None`を返します。
+    /// ルートなし・流動性不足のエラーはその入力量で上限を超えたものとして扱い、それ以外のエラーは返します。
+    pub async fn max_amount_within_impact(
+        &self,
+        curve: &QuoteCurve,
+        max_impact_bps: u32,
+        iterations: u32,
+    ) -> Result<Option<CurvePoint>> {
+        let max_impact_bps = max_impact_bps.min(BPS_DENOMINATOR);
+        let mut best = curve.max_within_impact(max_impact_bps).cloned();
+        let lower = best.as_ref().map_or(U256::zero(), |point| point.amount_in);
+        let Some(upper) = curve
+            .points
+            .iter()
+            .find(|point| point.amount_in > lower)
+            .map(|point| point.amount_in)
+        else {
+            return Ok(best);
+        };
+
+        let (mut low, mut high) = (lower, upper);
+        for _ in 0..iterations {
+            let middle = low + (high - low) / 2;
+            if middle == low {
+                break;
+            }
+
+            let params = FindRouterParams {
+                amount: middle,
+                ..curve.params.clone()
+            };
+            // ルートなし・流動性不足は上限を超えたものとして扱う
+            let data = match self.find_routers(params).await {
+                Ok(data) => data,
+                Err(e) if is_no_route(&e) => None,
+                Err(e) => return Err(e),
+            };
+            let point = data.and_then(|data| CurvePoint::new(data, best.as_ref()));
+            match point {
+                Some(point) if point.within_impact(max_impact_bps) => {
+                    low = middle;
+                    best = Some(point);
+                }
+                _ => high = middle,
+            }
+        }
+
+        Ok(best)
+    }
+}
+
+/// その入力量ではルートがないことを表すエラーかどうか（ルートなし・流動性不足）
+fn is_no_route(error: &AggregatorError) -> bool {
+    matches!(
+        error.server_error_code(),
+        Some(
+            AggregatorServerErrorCode::NoRouter | AggregatorServerErrorCode::InsufficientLiquidity
+        )
+    )
+}
+
+/// 使用するプールの組み合わせ（ルートごとのプールIDの並び）
+fn route_signature(data: &RouterData) -> Vec<Vec<&str>> {
+    let mut signature: Vec<Vec<&str>> = data
+        .routes
+        .iter()
+        .map(|route| route.path.iter().map(|path| path.id.as_str()).collect())
+        .collect();
+    signature.sort();
+    signature
+}
+
+/// 金額の近似値
+fn to_f64(amount: U256) -> f64 {
+    amount.to_string().parse().unwrap_or(f64::MAX)
+}
+
+/// 近似値から金額に変換（範囲外は上限・下限に丸める）
+fn from_f64(value: f64) -> U256 {
+    U256::from_dec_str(&format!("{:.0}", value.max(0.0))).unwrap_or(U256::MAX)
+}
@@ -0,0 +1,518 @@
+/**
+ * プロバイダー固有の拡張詳細情報
+ *
+ * このモジュールは`Path.extended_details`を、`Path.provider`に応じた型付きの詳細情報に変換します。
+ * プロバイダーごとに必須のフィールドを定義し、不足している場合はルートの検証で違反として報告します。
+ * 認識しないフィールドは生のJSONのまま保持するため、新しいプロバイダーが追加されても解析に失敗しません。
+ */
+use std::fmt;
+
+use serde::{Serialize, Serializer};
+use serde_json::{Map, Value};
+use thiserror::Error;
+
+use crate::locale::{global_locale, tr, Locale};
+use crate::models::Path;
+use crate::provider::Provider;
+
+/// 拡張詳細情報の変換エラー
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ExtendedDetailsError {
+    /// 必須フィールドが不足している
+    MissingFields {
+        /// プロバイダー
+        provider: Provider,
+        /// 不足しているフィールド
+        fields: Vec<&'static str>,
+    },
+
+    /// フィールドの型が不正
+    InvalidField {
+        /// フィールド名
+        field: &'static str,
+        /// 実際の値
+        value: String,
+    },
+}
+
+impl ExtendedDetailsError {
+    /// 指定したロケールで表示用の文字列に変換
+    pub fn to_localized_string(&self, locale: Locale) -> String {
+        match self {
+            Self::MissingFields { provider, fields } => tr!(
+                locale =>
+                "{}の拡張詳細情報に必須フィールドがありません: {}",
+                "extended details of {} are missing required fields: {}",
+                provider,
+                fields.join(", ")
+            ),
+            Self::InvalidField { field, value } => tr!(
+                locale =>
+                "拡張詳細情報のフィールド{}が不正です: {}",
+                "extended details field {} is invalid: {}",
+                field,
+                value
+            ),
+        }
+    }
+}
+
+/// プロセス全体のロケールで表示
+impl fmt::Display for ExtendedDetailsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_localized_string(global_locale()))
+    }
+}
+
+/// Aftermathの詳細情報
+#[derive(Debug, Clone, PartialEq)]
+pub struct AftermathDetails {
+    /// プールのフラットネス
+    pub pool_flatness: f64,
+    /// LPトークンの供給タイプ
+    pub lp_supply_type: String,
+}
+
+impl AftermathDetails {
+    /// 必須フィールド
+    pub const REQUIRED_FIELDS: &'static [&'static str] =
+        &["aftermath_pool_flatness", "aftermath_lp_supply_type"];
+}
+
+/// Turbosの詳細情報
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TurbosDetails {
+    /// 手数料タイプ
+    pub fee_type: String,
+}
+
+impl TurbosDetails {
+    /// 必須フィールド
+    pub const REQUIRED_FIELDS: &'static [&'static str] = &["turbos_fee_type"];
+}
+
+/// DeepBook v3の詳細情報
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeepbookV3Details {
+    /// DEEPで支払う手数料（DEEPで支払わない場合は`None`）
+    pub deep_fee: Option<f64>,
+}
+
+impl DeepbookV3Details {
+    /// 必須フィールド
+    pub const REQUIRED_FIELDS: &'static [&'static str] = &[];
+}
+
+/// Scallopの詳細情報
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScallopDetails {
+    /// sCoinのトレジャリー
+    pub scoin_treasury: String,
+}
+
+impl ScallopDetails {
+    /// 必須フィールド
+    pub const REQUIRED_FIELDS: &'static [&'static str] = &["scallop_scoin_treasury"];
+}
+
+/// Haedal PMMの詳細情報
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HaedalPmmDetails {
+    /// ベーストークンの価格シード
+    pub base_price_seed: String,
+    /// クォートトークンの価格シード
+    pub quote_price_seed: String,
+}
+
+impl HaedalPmmDetails {
+    /// 必須フィールド
+    pub const REQUIRED_FIELDS: &'static [&'static str] =
+        &["haedal_pmm_base_price_seed", "haedal_pmm_quote_price_seed"];
+}
+
+/// Steammの詳細情報
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SteammDetails {
+    /// コインAのバンク
+    pub bank_a: String,
+    /// コインBのバンク
+    pub bank_b: String,
+    /// レンディングマーケット
+    pub lending_market: String,
+    /// レンディングマーケットのタイプ
+    pub lending_market_type: String,
+    /// コインAのbTokenタイプ
+    pub btoken_a_type: String,
+    /// コインBのbTokenタイプ
+    pub btoken_b_type: String,
+    /// LPトークンのタイプ
+    pub lp_token_type: String,
+}
+
+impl SteammDetails {
+    /// 必須フィールド
+    pub const REQUIRED_FIELDS: &'static [&'static str] = &[
+        "steamm_bank_a",
+        "steamm_bank_b",
+        "steamm_lending_market",
+        "steamm_lending_market_type",
+        "steamm_btoken_a_type",
+        "steamm_btoken_b_type",
+        "steamm_lp_token_type",
+    ];
+}
+
+/// プロバイダー固有の詳細情報
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProviderDetails {
+    /// Aftermath
+    Aftermath(AftermathDetails),
+    /// Turbos
+    Turbos(TurbosDetails),
+    /// DeepBook v3
+    DeepbookV3(DeepbookV3Details),
+    /// Scallop
+    Scallop(ScallopDetails),
+    /// Haedal PMM
+    HaedalPmm(HaedalPmmDetails),
+    /// Steamm
+    Steamm(SteammDetails),
+    /// 固有の詳細情報を持たないプロバイダー
+    None,
+    /// 必須フィールドの不足または型の不正により変換できなかった詳細情報
+    ///
+    /// 受信したフィールドはすべて`extra`に保持されます。
+    Invalid(ExtendedDetailsError),
+}
+
+impl ProviderDetails {
+    /// プロバイダーごとの必須フィールド
+    pub fn required_fields(provider: &Provider) -> &'static [&'static str] {
+        match provider {
+            Provider::Aftermath => AftermathDetails::REQUIRED_FIELDS,
+            Provider::Turbos => TurbosDetails::REQUIRED_FIELDS,
+            Provider::DeepbookV3 => DeepbookV3Details::REQUIRED_FIELDS,
+            Provider::Scallop => ScallopDetails::REQUIRED_FIELDS,
+            Provider::HaedalPmm => HaedalPmmDetails::REQUIRED_FIELDS,
+            Provider::Steamm => SteammDetails::REQUIRED_FIELDS,
+            _ => &[],
+        }
+    }
+}
+
+/// 拡張詳細情報
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExtendedDetails {
+    /// プロバイダー固有の詳細情報
+    pub provider_details: ProviderDetails,
+    /// スワップ後の平方根価格（集中流動性プールの場合）
+    pub after_sqrt_price: Option<u128>,
+    /// 型付けされていないフィールド
+    pub extra: Map<String, Value>,
+}
+
+impl ExtendedDetails {
+    /// プロバイダーに応じて生のJSONから変換
+    ///
+    /// # 引数
+    ///
+    /// * `provider` - パスのプロバイダー
+    /// * `raw` - `extended_details`のJSONオブジェクト（ない場合は`None`）
+    ///
+    /// # 戻り値
+    ///
+    /// 拡張詳細情報。`raw`がなく、必須フィールドもないプロバイダーの場合は`None`を返します。
+    /// 必須フィールドが不足している場合、または型が不正な場合はエラーを返します。
+    pub fn from_raw(
+        provider: &Provider,
+        raw: Option<Map<String, Value>>,
+    ) -> Result<Option<Self>, ExtendedDetailsError> {
+        let Some(raw) = raw else {
+            let required = ProviderDetails::required_fields(provider);
+            if required.is_empty() {
+                return Ok(None);
+            }
+            return Err(ExtendedDetailsError::MissingFields {
+                provider: provider.clone(),
+                fields: required.to_vec(),
+            });
+        };
+
+        let mut fields = Fields::new(raw);
+        let after_sqrt_price = fields.optional_u128("after_sqrt_price")?;
+
+        let provider_details = match provider {
+            Provider::Aftermath => ProviderDetails::Aftermath(AftermathDetails {
+                pool_flatness: fields.required_f64("aftermath_pool_flatness")?,
+                lp_supply_type: fields.required_string("aftermath_lp_supply_type")?,
+            }),
+            Provider::Turbos => ProviderDetails::Turbos(TurbosDetails {
+                fee_type: fields.required_string("turbos_fee_type")?,
+            }),
+            Provider::DeepbookV3 => ProviderDetails::DeepbookV3(DeepbookV3Details {
+                deep_fee: fields.optional_f64("deepbookv3_deep_fee")?,
+            }),
+            Provider::Scallop => ProviderDetails::Scallop(ScallopDetails {
+                scoin_treasury: fields.required_string("scallop_scoin_treasury")?,
+            }),
+            Provider::HaedalPmm => ProviderDetails::HaedalPmm(HaedalPmmDetails {
+                base_price_seed: fields.required_string("haedal_pmm_base_price_seed")?,
+                quote_price_seed: fields.required_string("haedal_pmm_quote_price_seed")?,
+            }),
+            Provider::Steamm => ProviderDetails::Steamm(SteammDetails {
+                bank_a: fields.required_string("steamm_bank_a")?,
+                bank_b: fields.required_string("steamm_bank_b")?,
+                lending_market: fields.required_string("steamm_lending_market")?,
+                lending_market_type: fields.required_string("steamm_lending_market_type")?,
+                btoken_a_type: fields.required_string("steamm_btoken_a_type")?,
+                btoken_b_type: fields.required_string("steamm_btoken_b_type")?,
+                lp_token_type: fields.required_string("steamm_lp_token_type")?,
+            }),
+            _ => ProviderDetails::None,
+        };
+
+        let extra = fields.finish(provider)?;
+        Ok(Some(Self {
+            provider_details,
+            after_sqrt_price,
+            extra,
+        }))
+    }
+
+    /// プロバイダーに応じて生のJSONから変換し、変換できない場合も受信したフィールドを保持
+    ///
+    /// 必須フィールドの不足や型の不正は[`ProviderDetails::Invalid`]として記録し、
+    /// 受信したフィールドはすべて`extra`に残します。
+    /// `raw`がない場合は`None`を返します（必須フィールドの不足は[`Path::extended_details_error`]で確認できます）。
+    pub fn from_raw_lossy(provider: &Provider, raw: Option<Map<String, Value>>) -> Option<Self> {
+        let raw = raw?;
+        match Self::from_raw(provider, Some(raw.clone())) {
+            Ok(details) => details,
+            Err(error) => {
+                let mut extra = raw;
+                let after_sqrt_price = extra
+                    .get("after_sqrt_price")
+                    .and_then(number_string)
+                    .and_then(|s| s.parse::<u128>().ok());
+                if after_sqrt_price.is_some() {
+                    extra.remove("after_sqrt_price");
+                }
+                Some(Self {
+                    provider_details: ProviderDetails::Invalid(error),
+                    after_sqrt_price,
+                    extra,
+                })
+            }
+        }
+    }
+
+    /// APIと同じ形式のJSONオブジェクトに変換
+    pub fn to_raw(&self) -> Map<String, Value> {
+        let mut raw = self.extra.clone();
+        let mut insert = |key: &str, value: Value| {
+            raw.insert(key.to_string(), value);
+        };
+
+        if let Some(after_sqrt_price) = self.after_sqrt_price {
+            insert(
+                "after_sqrt_price",
+                Value::String(after_sqrt_price.to_string()),
+            );
+        }
+
+        match &self.provider_details {
+            ProviderDetails::Aftermath(details) => {
+                insert(
+                    "aftermath_pool_flatness",
+                    Value::from(details.pool_flatness),
+                );
+                insert(
+                    "aftermath_lp_supply_type",
+                    Value::from(details.lp_supply_type.clone()),
+                );
+            }
+            ProviderDetails::Turbos(details) => {
+                insert("turbos_fee_type", Value::from(details.fee_type.clone()));
+            }
+            ProviderDetails::DeepbookV3(details) => {
+                if let Some(deep_fee) = details.deep_fee {
+                    insert("deepbookv3_deep_fee", Value::from(deep_fee));
+                }
+            }
+            ProviderDetails::Scallop(details) => {
+                insert(
+                    "scallop_scoin_treasury",
+                    Value::from(details.scoin_treasury.clone()),
+                );
+            }
+            ProviderDetails::HaedalPmm(details) => {
+                insert(
+                    "haedal_pmm_base_price_seed",
+                    Value::from(details.base_price_seed.clone()),
+                );
+                insert(
+                    "haedal_pmm_quote_price_seed",
+                    Value::from(details.quote_price_seed.clone()),
+                );
+            }
+            ProviderDetails::Steamm(details) => {
+                insert("steamm_bank_a", Value::from(details.bank_a.clone()));
+                insert("steamm_bank_b", Value::from(details.bank_b.clone()));
+                insert(
+                    "steamm_lending_market",
+                    Value::from(details.lending_market.clone()),
+                );
+                insert(
+                    "steamm_lending_market_type",
+                    Value::from(details.lending_market_type.clone()),
+                );
+                insert(
+                    "steamm_btoken_a_type",
+                    Value::from(details.btoken_a_type.clone()),
+                );
+                insert(
+                    "steamm_btoken_b_type",
+                    Value::from(details.btoken_b_type.clone()),
+                );
+                insert(
+                    "steamm_lp_token_type",
+                    Value::from(details.lp_token_type.clone()),
+                );
+            }
+            ProviderDetails::None | ProviderDetails::Invalid(_) => {}
+        }
+
+        raw
+    }
+}
+
+impl Path {
+    /// 拡張詳細情報の不足または不正を取得
+    ///
+    /// プロバイダーの必須フィールドが不足している場合（`extended_details`がない場合を含む）、
+    /// またはフィールドの型が不正な場合にエラーを返します。
+    pub fn extended_details_error(&self) -> Option<ExtendedDetailsError> {
+        match &self.extended_details {
+            Some(ExtendedDetails {
+                provider_details: ProviderDetails::Invalid(error),
+                ..
+            }) => Some(error.clone()),
+            Some(_) => None,
+            None => {
+                let required = ProviderDetails::required_fields(&self.provider);
+                (!required.is_empty()).then(|| ExtendedDetailsError::MissingFields {
+                    provider: self.provider.clone(),
+                    fields: required.to_vec(),
+                })
+            }
+        }
+    }
+}
+
+impl Serialize for ExtendedDetails {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.to_raw().serialize(serializer)
+    }
+}
+
+/// JSONオブジェクトから必要なフィールドを取り出すヘルパー
+///
+/// 不足している必須フィールドを記録し、最後にまとめてエラーにします。
+struct Fields {
+    raw: Map<String, Value>,
+    missing: Vec<&'static str>,
+}
+
+impl Fields {
+    fn new(raw: Map<String, Value>) -> Self {
+        Self {
+            raw,
+            missing: Vec::new(),
+        }
+    }
+
+    /// フィールドを取り出す（`null`は存在しないものとして扱う）
+    fn take(&mut self, field: &str) -> Option<Value> {
+        self.raw.remove(field).filter(|value| !value.is_null())
+    }
+
+    fn optional_string(
+        &mut self,
+        field: &'static str,
+    ) -> Result<Option<String>, ExtendedDetailsError> {
+        match self.take(field) {
+            None => Ok(None),
+            Some(Value::String(s)) => Ok(Some(s)),
+            Some(other) => Err(invalid(field, &other)),
+        }
+    }
+
+    fn required_string(&mut self, field: &'static str) -> Result<String, ExtendedDetailsError> {
+        let value = self.optional_string(field)?;
+        Ok(self.require(field, value))
+    }
+
+    fn optional_f64(&mut self, field: &'static str) -> Result<Option<f64>, ExtendedDetailsError> {
+        match self.take(field) {
+            None => Ok(None),
+            Some(value) => number_string(&value)
+                .and_then(|s| s.parse::<f64>().ok())
+                .map(Some)
+                .ok_or_else(|| invalid(field, &value)),
+        }
+    }
+
+    fn required_f64(&mut self, field: &'static str) -> Result<f64, ExtendedDetailsError> {
+        let value = self.optional_f64(field)?;
+        Ok(self.require(field, value))
+    }
+
+    fn optional_u128(&mut self, field: &'static str) -> Result<Option<u128>, ExtendedDetailsError> {
+        match self.take(field) {
+            None => Ok(None),
+            Some(value) => number_string(&value)
+                .and_then(|s| s.parse::<u128>().ok())
+                .map(Some)
+                .ok_or_else(|| invalid(field, &value)),
+        }
+    }
+
+    /// 必須フィールドが存在しない場合は不足として記録し、仮の値を返す
+    fn require<T: Default>(&mut self, field: &'static str, value: Option<T>) -> T {
+        value.unwrap_or_else(|| {
+            self.missing.push(field);
+            T::default()
+        })
+    }
+
+    /// 不足しているフィールドがあればエラーを返し、なければ残りのフィールドを返す
+    fn finish(self, provider: &Provider) -> Result<Map<String, Value>, ExtendedDetailsError> {
+        if self.missing.is_empty() {
+            Ok(self.raw)
+        } else {
+            Err(ExtendedDetailsError::MissingFields {
+                provider: provider.clone(),
+                fields: self.missing,
+            })
+        }
+    }
+}
+
+/// 数値または数値の文字列を文字列として取得
+fn number_string(value: &Value) -> Option<String> {
+    match value {
+        Value::Number(n) => Some(n.to_string()),
+        Value::String(s) => Some(s.trim().to_string()),
+        _ => None,
+    }
+}
+
+fn invalid(field: &'static str, value: &Value) -> ExtendedDetailsError {
+    ExtendedDetailsError::InvalidField {
+        field,
+        value: value.to_string(),
+    }
+}
@@ -0,0 +1,421 @@
+/**
+ * エンドポイント管理
+ *
+ * このモジュールは複数エンドポイントのフェイルオーバーとヘッジリクエストのための
+ * エンドポイント一覧と健全性の追跡、エンドポイントごとのサーキットブレーカーを実装します。
+ */
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// 不健全と判定するまでの連続失敗回数のデフォルト値
+pub const DEFAULT_UNHEALTHY_THRESHOLD: u32 = 3;
+
+/// 不健全なエンドポイントを再び試すまでの、最後の失敗からの経過時間のデフォルト値
+pub const DEFAULT_RECOVERY_INTERVAL: Duration = Duration::from_secs(30);
+
+/// 平均レイテンシ（指数移動平均）の平滑化係数
+const LATENCY_EWMA_ALPHA: f64 = 0.2;
+
+/// 複数エンドポイントへのリクエスト方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RequestStrategy {
+    /// 健全なエンドポイントから順に送信し、再試行可能なエラーの場合は次のエンドポイントへ切り替える
+    #[default]
+    Failover,
+    /// 最優先のエンドポイントへ送信し、`delay`経過しても応答がなければ次のエンドポイントにも送信して、
+    /// 先に返ってきた応答を採用する
+    Hedged {
+        /// 2つ目のリクエストを送信するまでの待機時間
+        delay: Duration,
+    },
+}
+
+/// サーキットブレーカーの設定
+///
+/// 連続失敗回数またはタイムアウト率がしきい値に達したエンドポイントへの送信を`open_duration`の間停止し、
+/// [`AggregatorError::CircuitOpen`](crate::error::AggregatorError::CircuitOpen)で即座に失敗させます。
+/// 停止期間が過ぎると1件だけ試験的に送信し（半開状態）、成功すれば送信を再開します。
+#[derive(Debug, Clone, PartialEq)]
+pub struct CircuitBreaker {
+    /// 回路を開くまでの連続失敗回数
+    pub failure_threshold: u32,
+    /// 回路を開くタイムアウト率（0.0〜1.0、`None`の場合はタイムアウト率で判定しない）
+    pub timeout_rate_threshold: Option<f64>,
+    /// タイムアウト率を計算する直近のリクエスト数
+    pub window_size: usize,
+    /// 回路を開いてから試験的な送信を行うまでの時間
+    pub open_duration: Duration,
+}
+
+impl Default for CircuitBreaker {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            timeout_rate_threshold: Some(0.5),
+            window_size: 20,
+            open_duration: Duration::from_secs(30),
+        }
+    }
+}
+
+/// サーキットブレーカーの状態
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// 通常どおり送信する
+    Closed,
+    /// 送信を停止している
+    Open {
+        /// 試験的な送信を行うまでの残り時間（0の場合は次のリクエストを試験的に送信）
+        remaining: Duration,
+    },
+    /// 試験的な送信の結果を待っている
+    HalfOpen,
+}
+
+/// 回路の内部状態
+#[derive(Debug, Clone, Copy, Default)]
+enum Circuit {
+    #[default]
+    Closed,
+    Open {
+        until: Instant,
+    },
+    HalfOpen {
+        probe_started_at: Instant,
+    },
+}
+
+/// エンドポイントの健全性のスナップショット
+#[derive(Debug, Clone, PartialEq)]
+pub struct EndpointStatus {
+    /// エンドポイントURL
+    pub url: String,
+    /// 成功回数
+    pub successes: u64,
+    /// 失敗回数
+    pub failures: u64,
+    /// 連続失敗回数
+    pub consecutive_failures: u32,
+    /// 平均レイテンシ（指数移動平均）
+    pub average_latency: Option<Duration>,
+    /// 最後に発生したエラー
+    pub last_error: Option<String>,
+    /// 最後に失敗してからの経過時間
+    pub since_last_failure: Option<Duration>,
+    /// 健全と判定されているかどうか
+    ///
+    /// 連続失敗回数がしきい値に達していても、最後の失敗から回復間隔が経過していれば健全とみなします。
+    pub healthy: bool,
+    /// サーキットブレーカーの状態（サーキットブレーカーが無効の場合は常に`Closed`）
+    pub circuit: CircuitState,
+}
+
+/// 健全性の内部状態
+#[derive(Debug, Default)]
+struct HealthState {
+    successes: u64,
+    failures: u64,
+    consecutive_failures: u32,
+    average_latency: Option<Duration>,
+    last_error: Option<String>,
+    last_failure_at: Option<Instant>,
+    circuit: Circuit,
+    /// 直近のリクエストがタイムアウトしたかどうか（タイムアウト率の計算用）
+    recent_timeouts: VecDeque<bool>,
+}
+
+/// エンドポイントとその健全性
+#[derive(Debug)]
+pub(crate) struct Endpoint {
+    /// エンドポイントURL
+    pub(crate) url: String,
+    /// 健全性の状態
+    health: Mutex<HealthState>,
+    /// サーキットブレーカーの設定
+    breaker: Option<CircuitBreaker>,
+}
+
+impl Endpoint {
+    fn new(url: String, breaker: Option<CircuitBreaker>) -> Self {
+        Self {
+            url,
+            health: Mutex::new(HealthState::default()),
+            breaker,
+        }
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, HealthState> {
+        self.health.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// 送信を停止している場合に、試験的な送信を行えるようになるまでの残り時間を返す
+    ///
+    /// 回路の状態は変更しません。
+    pub(crate) fn blocked_for(&self) -> Option<Duration> {
+        let breaker = self.breaker.as_ref()?;
+        Self::remaining(breaker, &self.state(), Instant::now())
+    }
+
+    fn remaining(breaker: &CircuitBreaker, state: &HealthState, now: Instant) -> Option<Duration> {
+        match state.circuit {
+            Circuit::Closed => None,
+            Circuit::Open { until } => until.checked_duration_since(now).filter(|d| !d.is_zero()),
+            // 試験的な送信の結果を待っている間は、結果が記録されないまま失われた場合に備えて一定時間後に再度許可する
+            Circuit::HalfOpen { probe_started_at } => breaker
+                .open_duration
+                .checked_sub(now.duration_since(probe_started_at))
+                .filter(|d| !d.is_zero()),
+        }
+    }
+
+    /// 送信してよいかをサーキットブレーカーに問い合わせる
+    ///
+    /// 送信を停止している場合は、試験的な送信を行うまでの残り時間を返します。
+    /// 許可した送信の結果は[`Permit`]で記録し、記録せずにドロップした場合は試験的な送信を返却します。
+    pub(crate) fn try_acquire(&self) -> Result<Permit<'_>, Duration> {
+        let mut permit = Permit {
+            endpoint: self,
+            probe: false,
+        };
+        let Some(ref breaker) = self.breaker else {
+            return Ok(permit);
+        };
+        let mut state = self.state();
+        let now = Instant::now();
+
+        if let Some(remaining) = Self::remaining(breaker, &state, now) {
+            return Err(remaining);
+        }
+        if !matches!(state.circuit, Circuit::Closed) {
+            state.circuit = Circuit::HalfOpen {
+                probe_started_at: now,
+            };
+            permit.probe = true;
+        }
+        Ok(permit)
+    }
+
+    /// サーキットブレーカーの状態
+    fn circuit_state(state: &HealthState) -> CircuitState {
+        match state.circuit {
+            Circuit::Closed => CircuitState::Closed,
+            Circuit::Open { until } => CircuitState::Open {
+                remaining: until.saturating_duration_since(Instant::now()),
+            },
+            Circuit::HalfOpen { .. } => CircuitState::HalfOpen,
+        }
+    }
+
+    /// 直近のリクエストのタイムアウトの有無を記録
+    fn push_outcome(&self, state: &mut HealthState, timed_out: bool) {
+        let Some(ref breaker) = self.breaker else {
+            return;
+        };
+        state.recent_timeouts.push_back(timed_out);
+        while state.recent_timeouts.len() > breaker.window_size {
+            state.recent_timeouts.pop_front();
+        }
+    }
+
+    /// 成功を記録
+    fn record_success(&self, latency: Duration) {
+        let mut state = self.state();
+        state.successes += 1;
+        state.consecutive_failures = 0;
+        self.push_outcome(&mut state, false);
+        if let Circuit::HalfOpen { .. } = state.circuit {
+            state.circuit = Circuit::Closed;
+            state.recent_timeouts.clear();
+        }
+        state.average_latency = Some(match state.average_latency {
+            Some(average) => {
+                average.mul_f64(1.0 - LATENCY_EWMA_ALPHA) + latency.mul_f64(LATENCY_EWMA_ALPHA)
+            }
+            None => latency,
+        });
+    }
+
+    /// 健全性を判断できない結果で試験的な送信を終える
+    ///
+    /// 半開状態の場合は回路の状態を試験的な送信の前に戻し、次のリクエストで改めて試験的に送信します。
+    fn release(&self) {
+        let mut state = self.state();
+        if let Circuit::HalfOpen { .. } = state.circuit {
+            state.circuit = Circuit::Open {
+                until: Instant::now(),
+            };
+        }
+    }
+
+    /// 失敗を記録
+    ///
+    /// サーキットブレーカーのしきい値に達した場合、または試験的な送信が失敗した場合は回路を開きます。
+    fn record_failure(&self, error: &str, timed_out: bool) {
+        let mut state = self.state();
+        let now = Instant::now();
+        state.failures += 1;
+        state.consecutive_failures = state.consecutive_failures.saturating_add(1);
+        state.last_error = Some(error.to_string());
+        state.last_failure_at = Some(now);
+        self.push_outcome(&mut state, timed_out);
+
+        let Some(ref breaker) = self.breaker else {
+            return;
+        };
+        let should_open = match state.circuit {
+            Circuit::HalfOpen { .. } => true,
+            Circuit::Open { .. } => false,
+            Circuit::Closed => {
+                let window_full = state.recent_timeouts.len() >= breaker.window_size.max(1);
+                let timeout_rate = state.recent_timeouts.iter().filter(|t| **t).count() as f64
+                    / state.recent_timeouts.len().max(1) as f64;
+                state.consecutive_failures >= breaker.failure_threshold.max(1)
+                    || breaker
+                        .timeout_rate_threshold
+                        .is_some_and(|threshold| window_full && timeout_rate >= threshold)
+            }
+        };
+        if should_open {
+            state.circuit = Circuit::Open {
+                until: now + breaker.open_duration,
+            };
+            state.recent_timeouts.clear();
+        }
+    }
+
+    /// 回路が開いていて、まだ試験的な送信を行えないかどうか
+    fn is_open(&self) -> bool {
+        matches!(self.state().circuit, Circuit::Open { until } if Instant::now() < until)
+    }
+
+    fn consecutive_failures(&self) -> u32 {
+        self.state().consecutive_failures
+    }
+
+    /// 連続失敗回数がしきい値未満、または最後の失敗から`recovery_interval`が経過しているかどうか
+    fn is_healthy(&self, threshold: u32, recovery_interval: Duration) -> bool {
+        let state = self.state();
+        state.consecutive_failures < threshold
+            || state
+                .last_failure_at
+                .is_some_and(|at| at.elapsed() >= recovery_interval)
+    }
+}
+
+/// サーキットブレーカーが許可した1件の送信
+///
+/// 結果を記録せずにドロップした場合（送信がキャンセルされた場合など）、
+/// 試験的な送信であれば直ちに返却し、次のリクエストで改めて試験的に送信できるようにします。
+#[derive(Debug)]
+pub(crate) struct Permit<'a> {
+    endpoint: &'a Endpoint,
+    /// 半開状態の試験的な送信かどうか
+    probe: bool,
+}
+
+impl Permit<'_> {
+    /// 成功を記録
+    pub(crate) fn record_success(mut self, latency: Duration) {
+        self.probe = false;
+        self.endpoint.record_success(latency);
+    }
+
+    /// 失敗を記録
+    pub(crate) fn record_failure(mut self, error: &str, timed_out: bool) {
+        self.probe = false;
+        self.endpoint.record_failure(error, timed_out);
+    }
+}
+
+impl Drop for Permit<'_> {
+    fn drop(&mut self) {
+        if self.probe {
+            self.endpoint.release();
+        }
+    }
+}
+
+/// エンドポイントの一覧
+///
+/// クライアントのクローン間で共有され、健全性の統計も共有されます。
+#[derive(Debug)]
+pub(crate) struct EndpointSet {
+    /// 設定順のエンドポイント
+    endpoints: Vec<Endpoint>,
+    /// 不健全と判定するまでの連続失敗回数
+    unhealthy_threshold: u32,
+    /// 不健全なエンドポイントを再び試すまでの、最後の失敗からの経過時間
+    recovery_interval: Duration,
+}
+
+impl EndpointSet {
+    /// エンドポイント一覧を作成（`urls`は空でないこと）
+    pub(crate) fn new(
+        urls: Vec<String>,
+        unhealthy_threshold: u32,
+        recovery_interval: Duration,
+        breaker: Option<CircuitBreaker>,
+    ) -> Self {
+        debug_assert!(!urls.is_empty());
+        Self {
+            endpoints: urls
+                .into_iter()
+                .map(|url| Endpoint::new(url, breaker.clone()))
+                .collect(),
+            unhealthy_threshold: unhealthy_threshold.max(1),
+            recovery_interval,
+        }
+    }
+
+    /// 設定順で最初のエンドポイント
+    pub(crate) fn primary(&self) -> &Endpoint {
+        &self.endpoints[0]
+    }
+
+    /// 設定順のエンドポイントURL
+    pub(crate) fn urls(&self) -> Vec<String> {
+        self.endpoints.iter().map(|e| e.url.clone()).collect()
+    }
+
+    fn is_healthy(&self, endpoint: &Endpoint) -> bool {
+        endpoint.is_healthy(self.unhealthy_threshold, self.recovery_interval)
+    }
+
+    /// リクエストを送信する順序でエンドポイントを返す
+    ///
+    /// 健全なエンドポイントを設定順に並べ、その後に不健全なエンドポイントを
+    /// 連続失敗回数の少ない順に並べます。回路が開いているエンドポイントは最後になります。
+    /// 不健全なエンドポイントも最後の失敗から回復間隔が経過すると設定順の位置に戻り、
+    /// 次のリクエストで試されます（再び失敗した場合は次の回復間隔まで後回しになります）。
+    pub(crate) fn ordered(&self) -> Vec<&Endpoint> {
+        let (mut healthy, mut unhealthy): (Vec<&Endpoint>, Vec<&Endpoint>) =
+            self.endpoints.iter().partition(|e| self.is_healthy(e));
+        unhealthy.sort_by_key(|e| e.consecutive_failures());
+        healthy.append(&mut unhealthy);
+        healthy.sort_by_key(|e| e.is_open());
+        healthy
+    }
+
+    /// 各エンドポイントの健全性のスナップショット
+    pub(crate) fn status(&self) -> Vec<EndpointStatus> {
+        self.endpoints
+            .iter()
+            .map(|endpoint| {
+                let healthy = self.is_healthy(endpoint);
+                let state = endpoint.state();
+                EndpointStatus {
+                    url: endpoint.url.clone(),
+                    successes: state.successes,
+                    failures: state.failures,
+                    consecutive_failures: state.consecutive_failures,
+                    average_latency: state.average_latency,
+                    last_error: state.last_error.clone(),
+                    since_last_failure: state.last_failure_at.map(|at| at.elapsed()),
+                    healthy,
+                    circuit: Endpoint::circuit_state(&state),
+                }
+            })
+            .collect()
+    }
+}
@@ -3,8 +3,19 @@
  *
  * このモジュールはAPIとの通信時に発生する可能性のあるエラーを定義します。
  */
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use primitive_types::U256;
+use reqwest::header::HeaderMap;
 use thiserror::Error;
 
+use crate::coin::CoinType;
+use crate::locale::{global_locale, tr, Locale};
+use crate::package::PackageViolation;
+
 /// アグリゲーターAPIのエラーコード
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AggregatorServerErrorCode {
@@ -21,6 +32,15 @@ pub enum AggregatorServerErrorCode {
 }
 
 impl AggregatorServerErrorCode {
+    /// すべてのエラーコード
+    pub const ALL: &'static [AggregatorServerErrorCode] = &[
+        Self::CalculateError,
+        Self::NumberTooLarge,
+        Self::NoRouter,
+        Self::InsufficientLiquidity,
+        Self::HoneyPot,
+    ];
+
     /// エラーコードから対応するエラーコード列挙型を取得
     pub fn from_code(code: u32) -> Option<Self> {
         match code {
@@ -33,50 +53,606 @@ impl AggregatorServerErrorCode {
         }
     }
 
-    /// エラーコードに対応するメッセージを取得
+    /// 言語に依存しないエラーの識別子（`no_router`など）
+    pub fn id(&self) -> &'static str {
+        match self {
+            Self::CalculateError => "calculate_error",
+            Self::NumberTooLarge => "number_too_large",
+            Self::NoRouter => "no_router",
+            Self::InsufficientLiquidity => "insufficient_liquidity",
+            Self::HoneyPot => "honey_pot",
+        }
+    }
+
+    /// エラーコードに対応するメッセージを取得（プロセス全体のロケールを使用）
     pub fn message(&self) -> &'static str {
+        self.message_in(global_locale())
+    }
+
+    /// エラーコードに対応するメッセージを指定したロケールで取得
+    pub fn message_in(&self, locale: Locale) -> &'static str {
         match self {
-            Self::CalculateError => "計算エラーが発生しました",
-            Self::NumberTooLarge => "入力数値が大きすぎて対象の型に収まりません",
-            Self::NoRouter => "適切なルートが見つかりませんでした",
-            Self::InsufficientLiquidity => "流動性が不足しています",
-            Self::HoneyPot => "対象トークンがハニーポットスキャムとして検出されました",
+            Self::CalculateError => locale.select("計算エラーが発生しました", "calculation error"),
+            Self::NumberTooLarge => locale.select(
+                "入力数値が大きすぎて対象の型に収まりません",
+                "input number is too large to fit in the target type",
+            ),
+            Self::NoRouter => locale.select(
+                "適切なルートが見つかりませんでした",
+                "no suitable route was found",
+            ),
+            Self::InsufficientLiquidity => {
+                locale.select("流動性が不足しています", "insufficient liquidity")
+            }
+            Self::HoneyPot => locale.select(
+                "対象トークンがハニーポットスキャムとして検出されました",
+                "target token was detected as a honeypot scam",
+            ),
         }
     }
 }
 
+/// エラーが発生したリクエストの内容
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestContext {
+    /// 交換元コイン
+    pub from: CoinType,
+    /// 交換先コイン
+    pub target: CoinType,
+    /// 交換する金額
+    pub amount: U256,
+    /// 入力量ベースで計算したかどうか
+    pub by_amount_in: bool,
+    /// 送信先のAPIエンドポイント
+    pub endpoint: String,
+}
+
+impl RequestContext {
+    /// 指定したロケールで表示用の文字列に変換
+    pub fn to_localized_string(&self, locale: Locale) -> String {
+        tr!(
+            locale =>
+            "{} → {}, 金額: {}, 入力固定: {}, エンドポイント: {}",
+            "{} → {}, amount: {}, by_amount_in: {}, endpoint: {}",
+            self.from.to_short_string(),
+            self.target.to_short_string(),
+            self.amount,
+            self.by_amount_in,
+            self.endpoint
+        )
+    }
+}
+
+/// プロセス全体のロケールで表示
+impl fmt::Display for RequestContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_localized_string(global_locale()))
+    }
+}
+
+/// エラーに保持するレスポンス本文の最大バイト数
+pub const MAX_ERROR_BODY_BYTES: usize = 4096;
+
+/// エラーメッセージに表示する本文の最大文字数
+const DISPLAY_BODY_CHARS: usize = 200;
+
+/// JSONの解析エラーの位置の前後に表示する文字数
+const SNIPPET_CONTEXT_CHARS: usize = 40;
+
+/// エラー発生時のHTTPリクエストとレスポンスの内容
+///
+/// 本文は[`MAX_ERROR_BODY_BYTES`]バイトまでに切り詰めて保持します。
+#[derive(Debug, Clone)]
+pub struct HttpExchange {
+    /// リクエストのメソッド
+    pub method: String,
+    /// リクエストのURL（GETの場合はクエリパラメータを含む）
+    pub url: String,
+    /// リクエストの本文（POSTの場合のパラメータ）
+    pub request_body: Option<String>,
+    /// HTTPステータスコード
+    pub status: u16,
+    /// レスポンスヘッダー
+    pub headers: HeaderMap,
+    /// レスポンス本文（切り詰めたもの）
+    pub body: String,
+    /// 切り詰める前のレスポンス本文のバイト数
+    pub body_len: usize,
+}
+
+impl HttpExchange {
+    /// レスポンス本文が切り詰められているかどうか
+    pub fn is_truncated(&self) -> bool {
+        self.body.len() < self.body_len
+    }
+}
+
+/// 本文を最大バイト数までの文字列に変換（UTF-8として不正なバイトは置き換える）
+pub(crate) fn truncate_body(body: &[u8]) -> String {
+    let mut end = body.len().min(MAX_ERROR_BODY_BYTES);
+    // 切り詰めた位置がUTF-8の文字の途中にならないように調整
+    while end < body.len() && end > 0 && (body[end] & 0xC0) == 0x80 {
+        end -= 1;
+    }
+    String::from_utf8_lossy(&body[..end]).into_owned()
+}
+
+/// JSONの解析エラーの位置の前後の本文を取得
+pub(crate) fn json_error_snippet(body: &[u8], error: &serde_json::Error) -> String {
+    let text = String::from_utf8_lossy(body);
+    let line = text
+        .lines()
+        .nth(error.line().saturating_sub(1))
+        .unwrap_or("");
+    let chars: Vec<char> = line.chars().collect();
+    let column = error.column().min(chars.len());
+    let start = column.saturating_sub(SNIPPET_CONTEXT_CHARS);
+    let end = (column + SNIPPET_CONTEXT_CHARS).min(chars.len());
+
+    let mut snippet: String = chars[start..end].iter().collect();
+    if start > 0 {
+        snippet.insert(0, '…');
+    }
+    if end < chars.len() {
+        snippet.push('…');
+    }
+    snippet
+}
+
+/// 表示用に先頭の文字だけを取得
+fn excerpt(text: &str, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars) {
+        Some((i, _)) => format!("{}…", &text[..i]),
+        None => text.to_string(),
+    }
+}
+
 /// アグリゲーターAPIのエラー
+///
+/// `Display`はプロセス全体のロケール（[`set_global_locale`](crate::locale::set_global_locale)）で表示します。
+/// 別のロケールで表示する場合は[`localized`](Self::localized)、
+/// 言語に依存しない識別子は[`id`](Self::id)を使用してください。
 #[derive(Error, Debug)]
 pub enum AggregatorError {
     /// HTTPリクエストエラー
-    #[error("HTTPリクエストエラー: {0}")]
     RequestError(#[from] reqwest::Error),
 
     /// JSONシリアライズ/デシリアライズエラー
-    #[error("JSONエラー: {0}")]
     JsonError(#[from] serde_json::Error),
 
-    /// APIエラー
-    #[error("APIエラー ({code}): {message}")]
+    /// APIエラー（このクレートが認識しないエラーコード）
     ApiError {
         /// エラーコード
         code: u32,
         /// エラーメッセージ
         message: String,
+        /// リクエストの内容
+        context: Box<RequestContext>,
+    },
+
+    /// 計算エラー（10000）
+    CalculateError {
+        /// サーバーのエラーメッセージ
+        message: String,
+        /// リクエストの内容
+        context: Box<RequestContext>,
+    },
+
+    /// 入力数値が大きすぎる（10001）
+    NumberTooLarge {
+        /// サーバーのエラーメッセージ
+        message: String,
+        /// リクエストの内容
+        context: Box<RequestContext>,
+    },
+
+    /// ルートが見つからない（10002）
+    NoRouter {
+        /// サーバーのエラーメッセージ
+        message: String,
+        /// リクエストの内容
+        context: Box<RequestContext>,
+    },
+
+    /// 流動性不足（10003、または`insufficient_liquidity`が真のレスポンス）
+    InsufficientLiquidity {
+        /// サーバーのエラーメッセージ
+        message: String,
+        /// リクエストの内容
+        context: Box<RequestContext>,
+    },
+
+    /// ハニーポットスキャム検出（10004）
+    HoneyPot {
+        /// サーバーのエラーメッセージ
+        message: String,
+        /// リクエストの内容
+        context: Box<RequestContext>,
+    },
+
+    /// HTTPステータスエラー（2xx以外のレスポンス）
+    HttpError {
+        /// HTTPステータスコード
+        status: u16,
+        /// エラーメッセージ
+        message: String,
+        /// `Retry-After`ヘッダーで指定された待機時間
+        retry_after: Option<Duration>,
+        /// リクエストとレスポンスの内容（レスポンスを受信していない場合は`None`）
+        exchange: Option<Box<HttpExchange>>,
+    },
+
+    /// レスポンス本文をJSONとして解析できない
+    DecodeError {
+        /// JSONの解析エラー
+        #[source]
+        source: serde_json::Error,
+        /// エラー位置の前後の本文
+        snippet: String,
+        /// リクエストとレスポンスの内容
+        exchange: Box<HttpExchange>,
+    },
+
+    /// 許可リストにないパッケージがレスポンスに含まれている
+    PackageNotAllowed(Vec<PackageViolation>),
+
+    /// レスポンスの値が不正で、計算に使用できない
+    InvalidResponse(String),
+
+    /// サーキットブレーカーが開いているため送信しなかった
+    CircuitOpen {
+        /// 送信を停止しているエンドポイント
+        endpoint: String,
+        /// 試験的な送信を行えるようになるまでの時間
+        retry_in: Duration,
+    },
+
+    /// 再生モードのカセットに一致するリクエストが記録されていない
+    CassetteMismatch {
+        /// HTTPメソッド
+        method: String,
+        /// リクエストのパスとクエリ
+        url: String,
+    },
+
+    /// カセットファイルを読み書きできない
+    CassetteError {
+        /// カセットファイルのパス
+        path: PathBuf,
+        /// 読み書きのエラー
+        #[source]
+        source: Box<dyn std::error::Error + Send + Sync>,
     },
 
+    /// 同時に送信された同一のリクエストで発生し、複数の呼び出し元で共有されたエラー
+    ///
+    /// 識別子・表示・再試行の可否などは元のエラーと同じです。
+    Shared(#[source] Arc<AggregatorError>),
+
     /// サーバーエラー
-    #[error("サーバーエラー: {0}")]
     ServerError(#[source] anyhow::Error),
 
     /// 入力パラメータエラー
-    #[error("入力パラメータエラー: {0}")]
     InputError(String),
 
     /// その他のエラー
-    #[error("その他のエラー: {0}")]
     Other(#[from] anyhow::Error),
 }
 
+impl AggregatorError {
+    /// サーバーのエラーコードに対応するエラーを作成
+    ///
+    /// このクレートが認識しないエラーコードの場合は[`AggregatorError::ApiError`]になります。
+    pub fn from_server_code(code: u32, message: String, context: RequestContext) -> Self {
+        let context = Box::new(context);
+        match AggregatorServerErrorCode::from_code(code) {
+            Some(AggregatorServerErrorCode::CalculateError) => {
+                Self::CalculateError { message, context }
+            }
+            Some(AggregatorServerErrorCode::NumberTooLarge) => {
+                Self::NumberTooLarge { message, context }
+            }
+            Some(AggregatorServerErrorCode::NoRouter) => Self::NoRouter { message, context },
+            Some(AggregatorServerErrorCode::InsufficientLiquidity) => {
+                Self::InsufficientLiquidity { message, context }
+            }
+            Some(AggregatorServerErrorCode::HoneyPot) => Self::HoneyPot { message, context },
+            None => Self::ApiError {
+                code,
+                message,
+                context,
+            },
+        }
+    }
+
+    /// 言語に依存しないエラーの識別子
+    ///
+    /// サーバーのエラーは[`AggregatorServerErrorCode::id`]と同じ値（`no_router`など）です。
+    /// ログの集計やアラートの条件に使用できます。
+    pub fn id(&self) -> &'static str {
+        match self {
+            Self::RequestError(_) => "request_error",
+            Self::JsonError(_) => "json_error",
+            Self::ApiError { .. } => "api_error",
+            Self::CalculateError { .. } => AggregatorServerErrorCode::CalculateError.id(),
+            Self::NumberTooLarge { .. } => AggregatorServerErrorCode::NumberTooLarge.id(),
+            Self::NoRouter { .. } => AggregatorServerErrorCode::NoRouter.id(),
+            Self::InsufficientLiquidity { .. } => {
+                AggregatorServerErrorCode::InsufficientLiquidity.id()
+            }
+            Self::HoneyPot { .. } => AggregatorServerErrorCode::HoneyPot.id(),
+            Self::HttpError { .. } => "http_error",
+            Self::DecodeError { .. } => "decode_error",
+            Self::PackageNotAllowed(_) => "package_not_allowed",
+            Self::InvalidResponse(_) => "invalid_response",
+            Self::CircuitOpen { .. } => "circuit_open",
+            Self::CassetteMismatch { .. } => "cassette_mismatch",
+            Self::CassetteError { .. } => "cassette_error",
+            Self::Shared(e) => e.id(),
+            Self::ServerError(_) => "server_error",
+            Self::InputError(_) => "input_error",
+            Self::Other(_) => "other",
+        }
+    }
+
+    /// 指定したロケールで表示するためのラッパーを取得
+    ///
+    /// ```
+    /// use cetus_aggregator_rust::{AggregatorError, Locale};
+    ///
+    /// let error = AggregatorError::InputError("amount".to_string());
+    /// assert_eq!(error.id(), "input_error");
+    /// assert_eq!(
+    ///     error.localized(Locale::English).to_string(),
+    ///     "invalid input parameter: amount"
+    /// );
+    /// ```
+    pub fn localized(&self, locale: Locale) -> LocalizedError<'_> {
+        LocalizedError {
+            error: self,
+            locale,
+        }
+    }
+
+    /// 指定したロケールでエラーメッセージを書き込む
+    fn write_localized(&self, f: &mut fmt::Formatter<'_>, locale: Locale) -> fmt::Result {
+        let message = match self {
+            Self::Shared(e) => return e.write_localized(f, locale),
+            Self::RequestError(e) => {
+                tr!(locale => "HTTPリクエストエラー: {}", "HTTP request error: {}", e)
+            }
+            Self::JsonError(e) => tr!(locale => "JSONエラー: {}", "JSON error: {}", e),
+            Self::ApiError {
+                code,
+                message,
+                context,
+            } => tr!(
+                locale =>
+                "APIエラー ({}): {} ({})",
+                "API error ({}): {} ({})",
+                code,
+                message,
+                context.to_localized_string(locale)
+            ),
+            Self::CalculateError { message, context }
+            | Self::NumberTooLarge { message, context }
+            | Self::NoRouter { message, context }
+            | Self::InsufficientLiquidity { message, context }
+            | Self::HoneyPot { message, context } => format!(
+                "{}: {} ({})",
+                self.server_error_code()
+                    .map_or("", |code| code.message_in(locale)),
+                message,
+                context.to_localized_string(locale)
+            ),
+            Self::HttpError {
+                status,
+                message,
+                exchange,
+                ..
+            } => {
+                let mut text =
+                    tr!(locale => "HTTPエラー ({}): {}", "HTTP error ({}): {}", status, message);
+                if let Some(exchange) = exchange {
+                    text.push_str(&tr!(
+                        locale =>
+                        " ({} {}, 本文: {})",
+                        " ({} {}, body: {})",
+                        exchange.method,
+                        exchange.url,
+                        excerpt(&exchange.body, DISPLAY_BODY_CHARS)
+                    ));
+                }
+                text
+            }
+            Self::DecodeError {
+                source,
+                snippet,
+                exchange,
+            } => tr!(
+                locale =>
+                "レスポンスのJSONを解析できません: {} ({} {}, ステータス: {}, 位置: `{}`)",
+                "failed to decode response JSON: {} ({} {}, status: {}, near: `{}`)",
+                source,
+                exchange.method,
+                exchange.url,
+                exchange.status,
+                snippet
+            ),
+            Self::PackageNotAllowed(violations) => tr!(
+                locale =>
+                "許可されていないパッケージが含まれています: {}",
+                "response contains packages that are not allowed: {}",
+                join_violations(violations, locale)
+            ),
+            Self::InvalidResponse(message) => tr!(
+                locale =>
+                "レスポンスの内容が不正です: {}",
+                "invalid response: {}",
+                message
+            ),
+            Self::CircuitOpen { endpoint, retry_in } => tr!(
+                locale =>
+                "サーキットブレーカーが開いているため送信しませんでした: {}（{}ミリ秒後に再開）",
+                "circuit breaker is open, request not sent: {} (resumes in {} ms)",
+                endpoint,
+                retry_in.as_millis()
+            ),
+            Self::CassetteMismatch { method, url } => tr!(
+                locale =>
+                "カセットに一致するリクエストが記録されていません: {} {}",
+                "no matching request recorded in cassette: {} {}",
+                method,
+                url
+            ),
+            Self::CassetteError { path, source } => tr!(
+                locale =>
+                "カセットファイルを読み書きできません: {}: {}",
+                "failed to read or write cassette file: {}: {}",
+                path.display(),
+                source
+            ),
+            Self::ServerError(e) => tr!(locale => "サーバーエラー: {}", "server error: {}", e),
+            Self::InputError(message) => tr!(
+                locale =>
+                "入力パラメータエラー: {}",
+                "invalid input parameter: {}",
+                message
+            ),
+            Self::Other(e) => tr!(locale => "その他のエラー: {}", "other error: {}", e),
+        };
+        f.write_str(&message)
+    }
+
+    /// サーバーのエラーコード（サーバーのエラー以外は`None`）
+    pub fn server_error_code(&self) -> Option<AggregatorServerErrorCode> {
+        match self {
+            Self::CalculateError { .. } => Some(AggregatorServerErrorCode::CalculateError),
+            Self::NumberTooLarge { .. } => Some(AggregatorServerErrorCode::NumberTooLarge),
+            Self::NoRouter { .. } => Some(AggregatorServerErrorCode::NoRouter),
+            Self::InsufficientLiquidity { .. } => {
+                Some(AggregatorServerErrorCode::InsufficientLiquidity)
+            }
+            Self::HoneyPot { .. } => Some(AggregatorServerErrorCode::HoneyPot),
+            Self::ApiError { code, .. } => AggregatorServerErrorCode::from_code(*code),
+            Self::Shared(e) => e.server_error_code(),
+            _ => None,
+        }
+    }
+
+    /// エラーが発生したリクエストの内容（サーバーのエラー以外は`None`）
+    pub fn request_context(&self) -> Option<&RequestContext> {
+        match self {
+            Self::ApiError { context, .. }
+            | Self::CalculateError { context, .. }
+            | Self::NumberTooLarge { context, .. }
+            | Self::NoRouter { context, .. }
+            | Self::InsufficientLiquidity { context, .. }
+            | Self::HoneyPot { context, .. } => Some(context),
+            Self::Shared(e) => e.request_context(),
+            _ => None,
+        }
+    }
+
+    /// リトライによって成功する可能性のあるエラーかどうかを判定
+    ///
+    /// 5xx・408・429のHTTPエラー、タイムアウトや接続失敗、計算エラー（10000）は再試行可能です。
+    /// ルートなし・ハニーポット・入力パラメータエラーなど、結果が変わらないエラーは再試行しません。
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::RequestError(e) => e.is_timeout() || e.is_connect() || e.is_request(),
+            Self::HttpError { status, .. } => *status >= 500 || *status == 408 || *status == 429,
+            Self::CalculateError { .. } | Self::ServerError(_) => true,
+            Self::Shared(e) => e.is_retryable(),
+            Self::ApiError { .. }
+            | Self::NumberTooLarge { .. }
+            | Self::NoRouter { .. }
+            | Self::InsufficientLiquidity { .. }
+            | Self::HoneyPot { .. }
+            | Self::JsonError(_)
+            | Self::DecodeError { .. }
+            | Self::PackageNotAllowed(_)
+            | Self::InvalidResponse(_)
+            | Self::CircuitOpen { .. }
+            | Self::CassetteMismatch { .. }
+            | Self::CassetteError { .. }
+            | Self::InputError(_)
+            | Self::Other(_) => false,
+        }
+    }
+
+    /// エラーが発生したHTTPリクエストとレスポンスの内容
+    pub fn http_exchange(&self) -> Option<&HttpExchange> {
+        match self {
+            Self::HttpError { exchange, .. } => exchange.as_deref(),
+            Self::DecodeError { exchange, .. } => Some(exchange),
+            Self::Shared(e) => e.http_exchange(),
+            _ => None,
+        }
+    }
+
+    /// サーバーから指定された再試行までの待機時間を取得
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            Self::HttpError { retry_after, .. } => *retry_after,
+            Self::CircuitOpen { retry_in, .. } => Some(*retry_in),
+            Self::Shared(e) => e.retry_after(),
+            _ => None,
+        }
+    }
+}
+
+/// 失敗しない変換（`CoinType`から`CoinType`など）をエラー型に合わせるための変換
+impl From<std::convert::Infallible> for AggregatorError {
+    fn from(never: std::convert::Infallible) -> Self {
+        match never {}
+    }
+}
+
+/// プロセス全体のロケールで表示
+impl fmt::Display for AggregatorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_localized(f, global_locale())
+    }
+}
+
+/// 指定したロケールでエラーを表示するラッパー
+#[derive(Debug, Clone, Copy)]
+pub struct LocalizedError<'a> {
+    error: &'a AggregatorError,
+    locale: Locale,
+}
+
+impl fmt::Display for LocalizedError<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.error.write_localized(f, self.locale)
+    }
+}
+
+/// パッケージの違反を表示用に連結
+fn join_violations(violations: &[PackageViolation], locale: Locale) -> String {
+    violations
+        .iter()
+        .map(|violation| violation.to_localized_string(locale))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// `Retry-After`ヘッダーの値を待機時間に変換
+///
+/// 秒数形式とHTTP日付形式の両方に対応します。過去の日付の場合は0秒を返します。
+pub(crate) fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let date = httpdate::parse_http_date(value).ok()?;
+    Some(
+        date.duration_since(SystemTime::now())
+            .unwrap_or(Duration::ZERO),
+    )
+}
+
 /// Result型のエイリアス
 pub type Result<T> = std::result::Result<T, AggregatorError>;
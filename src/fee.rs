@@ -0,0 +1,120 @@
+/**
+ * 手数料の内訳
+ *
+ * このモジュールはルート検索結果から各ホップで支払う手数料を入力トークン単位で計算し、
+ * プロバイダーごとに集計した手数料レポートを作成します。
+ */
+use std::collections::BTreeMap;
+
+use primitive_types::U256;
+
+use crate::amount::amount_overflow;
+use crate::coin::CoinType;
+use crate::error::Result;
+use crate::models::{Path, RouterData};
+use crate::provider::Provider;
+use crate::quote::BPS_DENOMINATOR;
+use crate::ratio::Ratio;
+
+/// ホップごとの手数料
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HopFee {
+    /// ルートの位置
+    pub route: usize,
+    /// ルート内のホップの位置
+    pub hop: usize,
+    /// プロバイダー
+    pub provider: Provider,
+    /// プールID
+    pub pool_id: String,
+    /// 手数料を支払うコイン（ホップの入力コイン）
+    pub coin: CoinType,
+    /// 手数料率
+    pub fee_rate: Ratio,
+    /// 手数料額（入力トークンの最小単位、切り上げ）
+    pub amount: U256,
+}
+
+/// プロバイダー・コインごとの手数料の合計
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProviderFee {
+    /// プロバイダー
+    pub provider: Provider,
+    /// 手数料を支払うコイン
+    pub coin: CoinType,
+    /// 手数料額の合計
+    pub amount: U256,
+    /// 該当するホップ数
+    pub hops: usize,
+}
+
+/// 見積もりの手数料レポート
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct FeeReport {
+    /// ホップごとの手数料
+    pub hops: Vec<HopFee>,
+    /// プロバイダー・コインごとの手数料（プロバイダー順）
+    pub by_provider: Vec<ProviderFee>,
+}
+
+impl Path {
+    /// 手数料率をベーシスポイントで取得
+    ///
+    /// 計算結果が`U256`に収まらない場合は`None`を返します。
+    pub fn fee_bps(&self) -> Option<Ratio> {
+        self.fee_rate
+            .checked_mul(&Ratio::from_integer(U256::from(BPS_DENOMINATOR)))
+    }
+
+    /// このホップで支払う手数料額（入力トークンの最小単位、切り上げ）
+    ///
+    /// 計算結果が`U256`に収まらない場合は`None`を返します。
+    pub fn fee_amount(&self) -> Option<U256> {
+        self.fee_rate.mul_ceil(self.amount_in)
+    }
+}
+
+impl RouterData {
+    /// 各ホップの手数料と、プロバイダーごとの合計を計算
+    ///
+    /// 手数料は各ホップの入力量に手数料率を掛けた値（入力トークン単位）です。
+    /// ホップごとに入力コインが異なるため、合計はプロバイダーとコインの組ごとに集計します。
+    pub fn fee_report(&self) -> Result<FeeReport> {
+        let mut hops = Vec::new();
+        let mut totals: BTreeMap<(Provider, CoinType), (U256, usize)> = BTreeMap::new();
+
+        for (route_index, route) in self.routes.iter().enumerate() {
+            for (hop_index, path) in route.path.iter().enumerate() {
+                let amount = path.fee_amount().ok_or_else(amount_overflow)?;
+
+                let total = totals
+                    .entry((path.provider.clone(), path.from.clone()))
+                    .or_insert((U256::zero(), 0));
+                total.0 = total.0.checked_add(amount).ok_or_else(amount_overflow)?;
+                total.1 += 1;
+
+                hops.push(HopFee {
+                    route: route_index,
+                    hop: hop_index,
+                    provider: path.provider.clone(),
+                    pool_id: path.id.clone(),
+                    coin: path.from.clone(),
+                    fee_rate: path.fee_rate,
+                    amount,
+                });
+            }
+        }
+
+        let by_provider = totals
+            .into_iter()
+            .map(|((provider, coin), (amount, hops))| ProviderFee {
+                provider,
+                coin,
+                amount,
+                hops,
+            })
+            .collect();
+
+        Ok(FeeReport { hops, by_provider })
+    }
+}
@@ -0,0 +1,317 @@
+/**
+ * テスト用のルート検索結果の作成
+ *
+ * このモジュールは`test-util`フィーチャーで有効になり、
+ * 単体テストで使用する現実的な`RouterData`を組み立てるビルダーを提供します。
+ * 作成したデータは`RouterData::validate`による整合性検証を通過します。
+ */
+use primitive_types::U256;
+
+use crate::amount::checked_sum;
+use crate::coin::CoinType;
+use crate::details::ExtendedDetails;
+use crate::models::{Path, Router, RouterData, RouterError};
+use crate::package::PackageMap;
+use crate::provider::Provider;
+use crate::ratio::Ratio;
+
+/// デフォルトの手数料率（0.25%）
+const DEFAULT_FEE_RATE: (u64, u64) = (25, 10_000);
+
+/// プロバイダーとコインの組からプールIDを生成
+///
+/// 同じ組には常に同じIDを返すため、テストの実行順序によらず同じデータになります。
+/// 交換の向きによらず同じプールを指すよう、コインは順序を揃えてから使用します。
+fn pool_id(provider: &Provider, from: &CoinType, target: &CoinType) -> String {
+    let (from, target) = (from.to_string(), target.to_string());
+    let (a, b) = if from <= target {
+        (from, target)
+    } else {
+        (target, from)
+    };
+
+    // FNV-1a（Rustのバージョンによらず同じ値になるハッシュ）
+    let hash = [provider.to_string(), a, b]
+        .iter()
+        .flat_map(|part| part.bytes().chain([0]))
+        .fold(0xcbf2_9ce4_8422_2325u64, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        });
+    format!("0x{:064x}", hash)
+}
+
+/// [`Path`]（1ホップ）のビルダー
+#[derive(Debug, Clone)]
+pub struct PathBuilder {
+    path: Path,
+}
+
+impl PathBuilder {
+    /// プロバイダー・交換元・交換先・金額を指定してビルダーを作成
+    ///
+    /// プールIDはプロバイダーとコインの組から決まる値が設定され、手数料率は0.25%になります。
+    pub fn new(
+        provider: Provider,
+        from: CoinType,
+        target: CoinType,
+        amount_in: impl Into<U256>,
+        amount_out: impl Into<U256>,
+    ) -> Self {
+        Self {
+            path: Path {
+                id: pool_id(&provider, &from, &target),
+                direction: true,
+                provider,
+                from,
+                target,
+                fee_rate: Ratio::new(DEFAULT_FEE_RATE.0.into(), DEFAULT_FEE_RATE.1.into())
+                    .expect("分母は0ではない"),
+                amount_in: amount_in.into(),
+                amount_out: amount_out.into(),
+                version: None,
+                extended_details: None,
+            },
+        }
+    }
+
+    /// プールIDを設定
+    pub fn id(mut self, id: impl Into<String>) -> Self {
+        self.path.id = id.into();
+        self
+    }
+
+    /// 方向を設定
+    pub fn direction(mut self, direction: bool) -> Self {
+        self.path.direction = direction;
+        self
+    }
+
+    /// 手数料率を設定
+    pub fn fee_rate(mut self, fee_rate: Ratio) -> Self {
+        self.path.fee_rate = fee_rate;
+        self
+    }
+
+    /// バージョンを設定
+    pub fn version(mut self, version: impl Into<String>) -> Self {
+        self.path.version = Some(version.into());
+        self
+    }
+
+    /// 拡張詳細情報を設定
+    pub fn extended_details(mut self, extended_details: ExtendedDetails) -> Self {
+        self.path.extended_details = Some(extended_details);
+        self
+    }
+
+    /// パスを作成
+    pub fn build(self) -> Path {
+        self.path
+    }
+}
+
+/// [`Router`]（1ルート）のビルダー
+///
+/// ルートの入力量・出力量は最初と最後のホップから計算されます。
+#[derive(Debug, Clone, Default)]
+pub struct RouteBuilder {
+    path: Vec<Path>,
+    initial_price: Option<Ratio>,
+}
+
+impl RouteBuilder {
+    /// 空のビルダーを作成
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// ホップを追加
+    pub fn hop(mut self, path: Path) -> Self {
+        self.path.push(path);
+        self
+    }
+
+    /// 初期価格を設定
+    ///
+    /// 指定しない場合は、ルートの出力量/入力量（価格インパクトなし）になります。
+    pub fn initial_price(mut self, initial_price: Ratio) -> Self {
+        self.initial_price = Some(initial_price);
+        self
+    }
+
+    /// ルートを作成
+    pub fn build(self) -> Router {
+        let amount_in = self.path.first().map_or(U256::zero(), |hop| hop.amount_in);
+        let amount_out = self.path.last().map_or(U256::zero(), |hop| hop.amount_out);
+        let initial_price = self
+            .initial_price
+            .or_else(|| Ratio::new(amount_out, amount_in))
+            .unwrap_or_else(Ratio::zero);
+
+        Router {
+            path: self.path,
+            amount_in,
+            amount_out,
+            initial_price,
+        }
+    }
+}
+
+/// [`RouterData`]のビルダー
+///
+/// 総入力量・総出力量は各ルートの合計から計算されます。
+///
+/// ```
+/// use cetus_aggregator_rust::fixture::RouterDataBuilder;
+/// use cetus_aggregator_rust::{CoinType, FindRouterParams};
+///
+/// let sui = CoinType::sui();
+/// let usdc: CoinType = "0x5d4b302506645c37ff133b98c4b50a5ae14841659738d6d733d59d0d217a93bf::coin::COIN".parse().unwrap();
+///
+/// let data = RouterDataBuilder::swap(sui.clone(), usdc.clone(), 1_000_000_000u64, 3_500_000u64).build();
+/// let params = FindRouterParams::builder()
+///     .from(sui)
+///     .target(usdc)
+///     .amount(1_000_000_000u64)
+///     .build()
+///     .unwrap();
+/// assert!(data.validate(&params).is_ok());
+/// ```
+#[derive(Debug, Clone)]
+pub struct RouterDataBuilder {
+    routes: Vec<Router>,
+    by_amount_in: bool,
+    insufficient_liquidity: bool,
+    packages: Option<PackageMap>,
+    total_deep_fee: Option<f64>,
+    error: Option<RouterError>,
+}
+
+impl Default for RouterDataBuilder {
+    fn default() -> Self {
+        Self {
+            routes: Vec::new(),
+            by_amount_in: true,
+            insufficient_liquidity: false,
+            packages: None,
+            total_deep_fee: None,
+            error: None,
+        }
+    }
+}
+
+impl RouterDataBuilder {
+    /// 空のビルダーを作成（入力量ベース）
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cetusの1ホップのルートだけを含むビルダーを作成
+    pub fn swap(
+        from: CoinType,
+        target: CoinType,
+        amount_in: impl Into<U256>,
+        amount_out: impl Into<U256>,
+    ) -> Self {
+        Self::new().route(
+            RouteBuilder::new()
+                .hop(PathBuilder::new(Provider::Cetus, from, target, amount_in, amount_out).build())
+                .build(),
+        )
+    }
+
+    /// 複数のホップを経由する1ルートだけを含むビルダーを作成
+    ///
+    /// `coins`は経由するコインの順序（交換元から交換先まで）、
+    /// `amounts`は各コインの金額で、`coins`と同じ長さを指定します。
+    ///
+    /// # パニック
+    ///
+    /// `coins`と`amounts`の長さが異なる場合、または2未満の場合はパニックします。
+    pub fn multi_hop(provider: Provider, coins: &[CoinType], amounts: &[U256]) -> Self {
+        assert!(
+            coins.len() >= 2 && coins.len() == amounts.len(),
+            "coinsとamountsは同じ長さ（2以上）で指定してください"
+        );
+
+        let route = coins.windows(2).zip(amounts.windows(2)).fold(
+            RouteBuilder::new(),
+            |route, (pair, amount)| {
+                route.hop(
+                    PathBuilder::new(
+                        provider.clone(),
+                        pair[0].clone(),
+                        pair[1].clone(),
+                        amount[0],
+                        amount[1],
+                    )
+                    .build(),
+                )
+            },
+        );
+        Self::new().route(route.build())
+    }
+
+    /// ルートを追加
+    pub fn route(mut self, route: Router) -> Self {
+        self.routes.push(route);
+        self
+    }
+
+    /// 入力量ベースかどうかを設定
+    pub fn by_amount_in(mut self, by_amount_in: bool) -> Self {
+        self.by_amount_in = by_amount_in;
+        self
+    }
+
+    /// 流動性不足フラグを設定
+    pub fn insufficient_liquidity(mut self, insufficient_liquidity: bool) -> Self {
+        self.insufficient_liquidity = insufficient_liquidity;
+        self
+    }
+
+    /// パッケージマップを設定
+    pub fn packages(mut self, packages: PackageMap) -> Self {
+        self.packages = Some(packages);
+        self
+    }
+
+    /// DEEPで支払う手数料の総額を設定
+    pub fn total_deep_fee(mut self, total_deep_fee: f64) -> Self {
+        self.total_deep_fee = Some(total_deep_fee);
+        self
+    }
+
+    /// エラー情報を設定
+    pub fn error(mut self, code: u32, msg: impl Into<String>) -> Self {
+        self.error = Some(RouterError {
+            code,
+            msg: msg.into(),
+        });
+        self
+    }
+
+    /// ルート検索結果を作成
+    ///
+    /// # パニック
+    ///
+    /// ルートの金額の合計が`U256`に収まらない場合はパニックします。
+    pub fn build(self) -> RouterData {
+        let amount_in = checked_sum(self.routes.iter().map(|route| route.amount_in))
+            .expect("ルートの入力量の合計がオーバーフローしました");
+        let amount_out = checked_sum(self.routes.iter().map(|route| route.amount_out))
+            .expect("ルートの出力量の合計がオーバーフローしました");
+
+        RouterData {
+            amount_in,
+            amount_out,
+            by_amount_in: Some(self.by_amount_in),
+            routes: self.routes,
+            insufficient_liquidity: self.insufficient_liquidity,
+            packages: self.packages,
+            total_deep_fee: self.total_deep_fee,
+            error: self.error,
+            package_violations: Vec::new(),
+        }
+    }
+}
@@ -6,10 +6,12 @@
  *
  * # 使用例
  *
- * ```rust
- * use cetus_aggregator_rust::{AggregatorClient, AggregatorClientTrait, FindRouterParams};
- * use num_bigint::BigUint;
- * use std::str::FromStr;
+ * ```rust,no_run
+ * use cetus_aggregator_rust::{
+ *     AggregatorClient, AggregatorClientTrait, FindRouterParams, Provider, ProviderFilter,
+ *     SplitAlgorithm,
+ * };
+ * use primitive_types::U256;
  *
  * #[tokio::main]
  * async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -18,13 +20,13 @@
  *     
  *     // パラメータを準備
  *     let params = FindRouterParams {
- *         from: "0x2::sui::SUI".to_string(),
- *         target: "0x06864a6f921804860930db6ddbe2e16acdf8504495ea7481637a1c8b9a8fe54b::cetus::CETUS".to_string(),
- *         amount: BigUint::from_str("1000000000").unwrap(),
+ *         from: "0x2::sui::SUI".parse()?,
+ *         target: "0x06864a6f921804860930db6ddbe2e16acdf8504495ea7481637a1c8b9a8fe54b::cetus::CETUS".parse()?,
+ *         amount: U256::from(1000000000u64),
  *         by_amount_in: true,
  *         depth: Some(3),
- *         split_count: Some(1),
- *         providers: Some(vec!["CETUS".to_string()]),
+ *         split: Some(SplitAlgorithm::Auto { count: 1 }),
+ *         providers: ProviderFilter::Only(vec![Provider::Cetus]),
  *         ..Default::default()
  *     };
  *     
@@ -40,16 +42,72 @@
  *     Ok(())
  * }
  * ```
+ *
+ * # パッケージの検証
+ *
+ * このクレートには既知のパッケージIDを同梱しておらず、許可リストもプロバイダーごとには区別しません。
+ * [`AggregatorClientBuilder::package_allowlist`]で許可リストを設定するまで、レスポンスのパッケージIDは
+ * 検証されません。設定した場合は、ネットワークとパッケージ名ごとに固定したID以外をすべて違反として扱います。
+ * 詳細は[`package`]モジュールを参照してください。
  */
 // モジュールをエクスポート
+pub mod amount;
+pub mod batch;
+pub mod cache;
+pub mod cassette;
 pub mod client;
+pub mod coin;
+pub mod curve;
+pub mod details;
+pub mod endpoint;
 pub mod error;
+pub mod fee;
+#[cfg(feature = "test-util")]
+pub mod fixture;
+pub mod limit;
+pub mod locale;
+#[cfg(feature = "test-util")]
+pub mod mock;
+#[cfg(feature = "test-util")]
+pub mod mock_server;
 pub mod models;
+pub mod package;
+pub mod provider;
+pub mod quote;
+pub mod ratio;
+pub mod retry;
+pub mod split;
+pub mod validation;
 
 // 主要な型をルートレベルでエクスポート
-pub use client::{AggregatorClient, AggregatorClientTrait};
-pub use error::{AggregatorError, AggregatorServerErrorCode, Result};
+pub use batch::{BatchItem, DEFAULT_BATCH_CONCURRENCY};
+pub use cache::{AmountBucketing, CacheStats, QuoteCache};
+pub use cassette::{Cassette, CassetteMode};
+pub use client::{
+    AggregatorClient, AggregatorClientBuilder, AggregatorClientTrait, TlsBackend, DEFAULT_API_PATH,
+    DEFAULT_ENDPOINT, DEFAULT_SDK_VERSION,
+};
+pub use coin::{CoinType, TypeTag};
+pub use curve::{CurvePoint, QuoteCurve};
+pub use details::{ExtendedDetails, ExtendedDetailsError, ProviderDetails};
+pub use endpoint::{CircuitBreaker, CircuitState, EndpointStatus, RequestStrategy};
+pub use error::{
+    AggregatorError, AggregatorServerErrorCode, HttpExchange, LocalizedError, RequestContext,
+    Result, MAX_ERROR_BODY_BYTES,
+};
+pub use fee::{FeeReport, HopFee, ProviderFee};
+pub use limit::{LimiterStatus, Priority, RateLimit};
+pub use locale::{global_locale, set_global_locale, Locale};
 pub use models::{
-    AggregatorResponse, ExtendedDetails, FindRouterParams, Path, PreSwapLpChangeParams, Router,
-    RouterData, RouterError,
+    AggregatorResponse, FindRouterParams, FindRouterParamsBuilder, ParamLimits, Path,
+    PreSwapLpChangeParams, Router, RouterData, RouterError,
+};
+pub use package::{
+    Network, PackageAllowlist, PackageId, PackageMap, PackagePolicy, PackageViolation,
 };
+pub use provider::{Provider, ProviderFilter};
+pub use quote::PriceImpact;
+pub use ratio::Ratio;
+pub use retry::RetryPolicy;
+pub use split::SplitAlgorithm;
+pub use validation::RouteViolation;
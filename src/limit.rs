@@ -0,0 +1,266 @@
+/**
+ * クライアント側のレート制限と同時実行数の制限
+ *
+ * このモジュールはトークンバケットによるレート制限と、同時に送信中のリクエスト数の上限を実装します。
+ * 制限を待つリクエストは優先度の高い順、同じ優先度の中では到着順に送信されます。
+ */
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use tokio::sync::Notify;
+
+use crate::locale::{tr, Locale};
+
+/// トークンバケットによるレート制限
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimit {
+    /// 1秒あたりに補充されるトークン数
+    rate: f64,
+    /// バケットの容量（連続して送信できるリクエスト数）
+    burst: u32,
+}
+
+impl RateLimit {
+    /// 1秒あたり`requests`回に制限（バケットの容量も`requests`）
+    pub fn per_second(requests: u32) -> Self {
+        Self {
+            rate: f64::from(requests),
+            burst: requests,
+        }
+    }
+
+    /// `period`あたり`requests`回に制限（バケットの容量も`requests`）
+    pub fn per_period(requests: u32, period: Duration) -> Self {
+        Self {
+            rate: f64::from(requests) / period.as_secs_f64(),
+            burst: requests,
+        }
+    }
+
+    /// バケットの容量（連続して送信できるリクエスト数）を設定
+    pub fn with_burst(mut self, burst: u32) -> Self {
+        self.burst = burst;
+        self
+    }
+
+    /// 1秒あたりに補充されるトークン数
+    pub fn rate(&self) -> f64 {
+        self.rate
+    }
+
+    /// バケットの容量
+    pub fn burst(&self) -> u32 {
+        self.burst
+    }
+
+    /// 設定が不正である理由
+    pub(crate) fn violation(&self, locale: Locale) -> Option<String> {
+        if !(self.rate.is_finite() && self.rate > 0.0) || self.burst == 0 {
+            Some(tr!(
+                locale =>
+                "レート制限には正のレートと1以上の容量を指定してください: {}/秒, 容量 {}",
+                "rate limit must have a positive rate and a burst of at least 1: {}/s, burst {}",
+                self.rate,
+                self.burst
+            ))
+        } else {
+            None
+        }
+    }
+}
+
+/// リクエストの優先度
+///
+/// 制限を待っているリクエストは優先度の高い順に送信されます。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum Priority {
+    /// バックグラウンドのスキャンなど
+    Low,
+    /// 通常
+    #[default]
+    Normal,
+    /// レイテンシが重要な見積もりなど
+    High,
+}
+
+/// 制限の状態のスナップショット
+#[derive(Debug, Clone, PartialEq)]
+pub struct LimiterStatus {
+    /// 送信中のリクエスト数
+    pub in_flight: usize,
+    /// 制限を待っているリクエスト数
+    pub queued: usize,
+    /// すぐに使用できるトークン数（レート制限がない場合は`None`）
+    pub available_tokens: Option<f64>,
+}
+
+/// 待機列の順番（優先度の高い順、同じ優先度は到着順）
+type Ticket = (Reverse<Priority>, u64);
+
+#[derive(Debug)]
+struct LimiterState {
+    tokens: f64,
+    last_refill: Instant,
+    in_flight: usize,
+    /// 待機列（リクエストごとの通知を持ち、先頭のリクエストだけを起こす）
+    queue: BTreeMap<Ticket, Arc<Notify>>,
+    next_sequence: u64,
+}
+
+impl LimiterState {
+    /// 待機列の先頭のリクエストに状態の再確認を通知
+    fn wake_head(&self) {
+        if let Some(notify) = self.queue.values().next() {
+            notify.notify_one();
+        }
+    }
+}
+
+/// レート制限と同時実行数の制限
+///
+/// クライアントのクローン間で共有されます。
+#[derive(Debug)]
+pub(crate) struct Limiter {
+    rate_limit: Option<RateLimit>,
+    max_in_flight: Option<usize>,
+    state: Mutex<LimiterState>,
+}
+
+impl Limiter {
+    pub(crate) fn new(rate_limit: Option<RateLimit>, max_in_flight: Option<usize>) -> Self {
+        Self {
+            rate_limit,
+            max_in_flight,
+            state: Mutex::new(LimiterState {
+                tokens: rate_limit.map_or(0.0, |limit| f64::from(limit.burst)),
+                last_refill: Instant::now(),
+                in_flight: 0,
+                queue: BTreeMap::new(),
+                next_sequence: 0,
+            }),
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, LimiterState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// 経過時間に応じてトークンを補充
+    fn refill(&self, state: &mut LimiterState) {
+        let Some(limit) = self.rate_limit else {
+            return;
+        };
+        let now = Instant::now();
+        let elapsed = now.duration_since(state.last_refill).as_secs_f64();
+        state.tokens = (state.tokens + elapsed * limit.rate).min(f64::from(limit.burst));
+        state.last_refill = now;
+    }
+
+    /// 送信の許可を取得するまで待機
+    ///
+    /// 待機中にキャンセルされた場合は待機列から外れます。
+    pub(crate) async fn acquire(&self, priority: Priority) -> Permit<'_> {
+        let notify = Arc::new(Notify::new());
+        let ticket = {
+            let mut state = self.state();
+            let ticket = (Reverse(priority), state.next_sequence);
+            state.next_sequence += 1;
+            state.queue.insert(ticket, Arc::clone(&notify));
+            ticket
+        };
+        let mut queued = QueuedTicket {
+            limiter: self,
+            ticket: Some(ticket),
+        };
+
+        loop {
+            // `notify_one`は待機を開始する前の通知も保持するため、確認後の通知を取りこぼさない
+            let wait = {
+                let mut state = self.state();
+                self.refill(&mut state);
+
+                if state.queue.keys().next() != Some(&ticket) {
+                    // 先頭のリクエストが送信されるまで待機
+                    None
+                } else if self.max_in_flight.is_some_and(|max| state.in_flight >= max) {
+                    // 送信中のリクエストが完了するまで待機
+                    None
+                } else if let Some(limit) = self.rate_limit.filter(|_| state.tokens < 1.0) {
+                    // 次のトークンが補充されるまで待機
+                    Some(Duration::from_secs_f64((1.0 - state.tokens) / limit.rate))
+                } else {
+                    if self.rate_limit.is_some() {
+                        state.tokens -= 1.0;
+                    }
+                    state.in_flight += 1;
+                    state.queue.remove(&ticket);
+                    queued.ticket = None;
+                    // 次のリクエストが先頭になったことを通知
+                    state.wake_head();
+                    return Permit { limiter: self };
+                }
+            };
+
+            match wait {
+                Some(delay) => {
+                    tokio::select! {
+                        _ = tokio::time::sleep(delay) => {}
+                        _ = notify.notified() => {}
+                    }
+                }
+                None => notify.notified().await,
+            }
+        }
+    }
+
+    /// 同時に送信するリクエスト数の上限
+    pub(crate) fn max_in_flight(&self) -> Option<usize> {
+        self.max_in_flight
+    }
+
+    /// 制限の状態を取得
+    pub(crate) fn status(&self) -> LimiterStatus {
+        let mut state = self.state();
+        self.refill(&mut state);
+        LimiterStatus {
+            in_flight: state.in_flight,
+            queued: state.queue.len(),
+            available_tokens: self.rate_limit.map(|_| state.tokens),
+        }
+    }
+}
+
+/// 待機列に並んでいるリクエスト（キャンセルされた場合に待機列から外す）
+struct QueuedTicket<'a> {
+    limiter: &'a Limiter,
+    ticket: Option<Ticket>,
+}
+
+impl Drop for QueuedTicket<'_> {
+    fn drop(&mut self) {
+        if let Some(ticket) = self.ticket.take() {
+            let mut state = self.limiter.state();
+            let was_head = state.queue.keys().next() == Some(&ticket);
+            state.queue.remove(&ticket);
+            // 先頭のリクエストがキャンセルされた場合は、次のリクエストに順番を渡す
+            if was_head {
+                state.wake_head();
+            }
+        }
+    }
+}
+
+/// 送信の許可（ドロップすると送信中のリクエスト数が減る）
+pub(crate) struct Permit<'a> {
+    limiter: &'a Limiter,
+}
+
+impl Drop for Permit<'_> {
+    fn drop(&mut self) {
+        let mut state = self.limiter.state();
+        state.in_flight -= 1;
+        state.wake_head();
+    }
+}
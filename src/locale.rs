@@ -0,0 +1,118 @@
+/**
+ * メッセージの言語設定
+ *
+ * このモジュールはエラーメッセージなどを表示する言語（ロケール）を定義します。
+ * ロケールはプロセス全体で設定でき、クライアントごとに上書きすることもできます。
+ */
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use crate::error::AggregatorError;
+
+/// ロケールに応じて日本語または英語のメッセージを作成
+///
+/// ロケールを省略した場合はプロセス全体のロケールを使用します。
+macro_rules! tr {
+    ($locale:expr => $japanese:literal, $english:literal $(, $arg:expr)* $(,)?) => {
+        match $locale {
+            $crate::locale::Locale::Japanese => format!($japanese $(, $arg)*),
+            $crate::locale::Locale::English => format!($english $(, $arg)*),
+        }
+    };
+    ($japanese:literal, $english:literal $(, $arg:expr)* $(,)?) => {
+        $crate::locale::tr!($crate::locale::global_locale() => $japanese, $english $(, $arg)*)
+    };
+}
+
+pub(crate) use tr;
+
+/// メッセージの言語
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Locale {
+    /// 日本語
+    #[default]
+    Japanese,
+    /// 英語
+    English,
+}
+
+impl Locale {
+    /// このクレートが対応しているすべてのロケール
+    pub const ALL: &'static [Locale] = &[Locale::Japanese, Locale::English];
+
+    /// 言語コード（`ja`、`en`）
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Japanese => "ja",
+            Self::English => "en",
+        }
+    }
+
+    /// 日本語と英語のうち、このロケールに対応するものを選択
+    pub fn select<T>(&self, japanese: T, english: T) -> T {
+        match self {
+            Self::Japanese => japanese,
+            Self::English => english,
+        }
+    }
+
+    fn to_u8(self) -> u8 {
+        match self {
+            Self::Japanese => 0,
+            Self::English => 1,
+        }
+    }
+
+    fn from_u8(value: u8) -> Self {
+        match value {
+            1 => Self::English,
+            _ => Self::Japanese,
+        }
+    }
+}
+
+impl fmt::Display for Locale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+/// 言語コードから変換（`ja`、`en`、`en-US`、`ja_JP.UTF-8`など。大文字・小文字は区別しない）
+impl FromStr for Locale {
+    type Err = AggregatorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let language = s
+            .trim()
+            .split(['-', '_', '.'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+
+        match language.as_str() {
+            "ja" | "japanese" => Ok(Self::Japanese),
+            "en" | "english" => Ok(Self::English),
+            _ => Err(AggregatorError::InputError(tr!(
+                "対応していない言語です: {}",
+                "unsupported locale: {}",
+                s
+            ))),
+        }
+    }
+}
+
+/// プロセス全体のロケール
+static GLOBAL_LOCALE: AtomicU8 = AtomicU8::new(0);
+
+/// プロセス全体のロケールを設定
+///
+/// クライアントにロケールが設定されていない場合や、エラーの`Display`で使用されます。
+pub fn set_global_locale(locale: Locale) {
+    GLOBAL_LOCALE.store(locale.to_u8(), Ordering::Relaxed);
+}
+
+/// プロセス全体のロケールを取得（デフォルトは日本語）
+pub fn global_locale() -> Locale {
+    Locale::from_u8(GLOBAL_LOCALE.load(Ordering::Relaxed))
+}
@@ -0,0 +1,367 @@
+/**
+ * テスト用のモッククライアント
+ *
+ * このモジュールは`test-util`フィーチャーで有効になり、
+ * HTTP通信を行わずに[`AggregatorClientTrait`]を実装するモッククライアントを提供します。
+ * 交換元・交換先・金額ごとの応答の設定、エラーの注入、遅延の付与、受信した呼び出しの記録ができます。
+ */
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use primitive_types::U256;
+
+use crate::client::{check_router_data, AggregatorClientTrait};
+use crate::coin::CoinType;
+use crate::error::{AggregatorError, AggregatorServerErrorCode, RequestContext, Result};
+use crate::locale::{global_locale, tr};
+use crate::models::{FindRouterParams, ParamLimits, RouterData};
+
+/// モックのエンドポイント名（エラーの`RequestContext`に使用）
+const MOCK_ENDPOINT: &str = "mock://aggregator";
+
+/// モックの呼び出しに対する`RequestContext`を作成
+fn mock_context(params: &FindRouterParams) -> RequestContext {
+    RequestContext {
+        from: params.from.clone(),
+        target: params.target.clone(),
+        amount: params.amount,
+        by_amount_in: params.by_amount_in,
+        endpoint: MOCK_ENDPOINT.to_string(),
+    }
+}
+
+/// エラーを作成する関数
+type ErrorFactory = Arc<dyn Fn(&FindRouterParams) -> AggregatorError + Send + Sync>;
+
+/// モッククライアントが返す応答
+#[derive(Clone)]
+pub enum MockResponse {
+    /// ルート検索結果を返す
+    Route(Box<RouterData>),
+    /// ルートなし（`Ok(None)`）を返す
+    NoRoute,
+    /// エラーを返す（呼び出しのパラメータからエラーを作成）
+    Error(ErrorFactory),
+    /// 指定した時間だけ待機してから応答を返す
+    Delayed(Duration, Box<MockResponse>),
+}
+
+impl MockResponse {
+    /// ルート検索結果を返す応答を作成
+    ///
+    /// `error`（コードが0以外）または`insufficient_liquidity`を含む場合は、
+    /// 実際のクライアントと同様にサーバーのエラーコードに対応するエラーを返します。
+    pub fn route(data: RouterData) -> Self {
+        Self::Route(Box::new(data))
+    }
+
+    /// ルートなしを返す応答を作成
+    pub fn no_route() -> Self {
+        Self::NoRoute
+    }
+
+    /// 呼び出しのパラメータからエラーを作成して返す応答を作成
+    pub fn error<F>(factory: F) -> Self
+    where
+        F: Fn(&FindRouterParams) -> AggregatorError + Send + Sync + 'static,
+    {
+        Self::Error(Arc::new(factory))
+    }
+
+    /// サーバーのエラーコードに対応するエラーを返す応答を作成
+    pub fn server_error(code: AggregatorServerErrorCode, message: impl Into<String>) -> Self {
+        let message = message.into();
+        Self::error(move |params| {
+            AggregatorError::from_server_code(code as u32, message.clone(), mock_context(params))
+        })
+    }
+
+    /// HTTPステータスエラーを返す応答を作成
+    pub fn http_error(status: u16, message: impl Into<String>) -> Self {
+        let message = message.into();
+        Self::error(move |_| AggregatorError::HttpError {
+            status,
+            message: message.clone(),
+            retry_after: None,
+            exchange: None,
+        })
+    }
+
+    /// 指定した時間だけ待機してからこの応答を返すように変換
+    pub fn delayed(self, delay: Duration) -> Self {
+        Self::Delayed(delay, Box::new(self))
+    }
+}
+
+impl fmt::Debug for MockResponse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Route(data) => f.debug_tuple("Route").field(data).finish(),
+            Self::NoRoute => f.write_str("NoRoute"),
+            Self::Error(_) => f.write_str("Error(..)"),
+            Self::Delayed(delay, response) => f
+                .debug_tuple("Delayed")
+                .field(delay)
+                .field(response)
+                .finish(),
+        }
+    }
+}
+
+/// モッククライアントが受信した呼び出し
+#[derive(Debug, Clone)]
+pub struct MockCall {
+    /// ルート検索のパラメータ
+    pub params: FindRouterParams,
+    /// 受信した時刻
+    pub received_at: Instant,
+}
+
+/// 交換元・交換先・金額ごとの応答の設定
+#[derive(Debug)]
+struct Script {
+    from: CoinType,
+    target: CoinType,
+    /// `None`の場合はすべての金額に一致
+    amount: Option<U256>,
+    /// 順に返す応答（最後の応答は繰り返し返す）
+    responses: VecDeque<MockResponse>,
+}
+
+impl Script {
+    fn matches(&self, params: &FindRouterParams) -> bool {
+        self.from == params.from
+            && self.target == params.target
+            && (self.amount.is_none() || self.amount == Some(params.amount))
+    }
+
+    fn next_response(&mut self) -> Option<MockResponse> {
+        if self.responses.len() > 1 {
+            self.responses.pop_front()
+        } else {
+            self.responses.front().cloned()
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct MockState {
+    scripts: Vec<Script>,
+    fallback: Option<MockResponse>,
+    injected: VecDeque<MockResponse>,
+    latency: Duration,
+    calls: Vec<MockCall>,
+}
+
+impl MockState {
+    /// 呼び出しに対する応答を選択
+    ///
+    /// 注入された応答、金額まで一致する設定、金額を問わない設定、フォールバックの順に探します。
+    fn next_response(&mut self, params: &FindRouterParams) -> Option<MockResponse> {
+        if let Some(response) = self.injected.pop_front() {
+            return Some(response);
+        }
+
+        let exact = self
+            .scripts
+            .iter()
+            .position(|script| script.amount.is_some() && script.matches(params));
+        let any = || {
+            self.scripts
+                .iter()
+                .position(|script| script.amount.is_none() && script.matches(params))
+        };
+        match exact.or_else(any) {
+            Some(index) => self.scripts[index].next_response(),
+            None => self.fallback.clone(),
+        }
+    }
+}
+
+/// HTTP通信を行わないモッククライアント
+///
+/// クローンしたクライアントは応答の設定と呼び出しの記録を共有します。
+/// 応答が設定されていない呼び出しには`InputError`を返します。
+///
+/// ```
+/// use cetus_aggregator_rust::fixture::RouterDataBuilder;
+/// use cetus_aggregator_rust::mock::{MockAggregatorClient, MockResponse};
+/// use cetus_aggregator_rust::{AggregatorClientTrait, CoinType, FindRouterParams};
+///
+/// # tokio_test::block_on(async {
+/// let sui = CoinType::sui();
+/// let usdc: CoinType = "0x5d4b302506645c37ff133b98c4b50a5ae14841659738d6d733d59d0d217a93bf::coin::COIN".parse().unwrap();
+///
+/// let mock = MockAggregatorClient::new();
+/// mock.respond(
+///     sui.clone(),
+///     usdc.clone(),
+///     MockResponse::route(RouterDataBuilder::swap(sui.clone(), usdc.clone(), 1_000u64, 3u64).build()),
+/// );
+///
+/// let params = FindRouterParams::builder().from(sui).target(usdc).amount(1_000u64).build().unwrap();
+/// let data = mock.find_routers(params).await.unwrap().unwrap();
+/// assert_eq!(data.amount_out, 3u64.into());
+/// assert_eq!(mock.call_count(), 1);
+/// # });
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MockAggregatorClient {
+    state: Arc<Mutex<MockState>>,
+}
+
+impl MockAggregatorClient {
+    /// 応答が設定されていないモッククライアントを作成
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn state(&self) -> MutexGuard<'_, MockState> {
+        // テスト中のパニックで汚染されても記録は参照できるようにする
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn push_script(
+        &self,
+        from: CoinType,
+        target: CoinType,
+        amount: Option<U256>,
+        response: MockResponse,
+    ) -> &Self {
+        let mut state = self.state();
+        match state.scripts.iter_mut().find(|script| {
+            script.from == from && script.target == target && script.amount == amount
+        }) {
+            Some(script) => script.responses.push_back(response),
+            None => state.scripts.push(Script {
+                from,
+                target,
+                amount,
+                responses: VecDeque::from([response]),
+            }),
+        }
+        drop(state);
+        self
+    }
+
+    /// 交換元・交換先が一致する呼び出しへの応答を追加（金額は問わない）
+    ///
+    /// 同じ組み合わせに複数回追加した場合は追加した順に返し、最後の応答を繰り返し返します。
+    pub fn respond(&self, from: CoinType, target: CoinType, response: MockResponse) -> &Self {
+        self.push_script(from, target, None, response)
+    }
+
+    /// 交換元・交換先・金額が一致する呼び出しへの応答を追加
+    ///
+    /// 金額まで一致する設定は、金額を問わない設定より優先されます。
+    pub fn respond_to_amount(
+        &self,
+        from: CoinType,
+        target: CoinType,
+        amount: impl Into<U256>,
+        response: MockResponse,
+    ) -> &Self {
+        self.push_script(from, target, Some(amount.into()), response)
+    }
+
+    /// どの設定にも一致しない呼び出しへの応答を設定
+    pub fn fallback(&self, response: MockResponse) -> &Self {
+        self.state().fallback = Some(response);
+        self
+    }
+
+    /// 次の呼び出しに、設定に関わらず指定した応答を返す
+    ///
+    /// 複数回呼び出した場合は、その回数分の呼び出しに順に返します。
+    pub fn inject(&self, response: MockResponse) -> &Self {
+        self.state().injected.push_back(response);
+        self
+    }
+
+    /// 次の呼び出しに、設定に関わらずエラーを返す
+    pub fn fail_next<F>(&self, factory: F) -> &Self
+    where
+        F: Fn(&FindRouterParams) -> AggregatorError + Send + Sync + 'static,
+    {
+        self.inject(MockResponse::error(factory))
+    }
+
+    /// すべての呼び出しに付与する遅延を設定
+    pub fn latency(&self, latency: Duration) -> &Self {
+        self.state().latency = latency;
+        self
+    }
+
+    /// 受信した呼び出しの一覧（受信順）
+    pub fn calls(&self) -> Vec<MockCall> {
+        self.state().calls.clone()
+    }
+
+    /// 受信した呼び出しの回数
+    pub fn call_count(&self) -> usize {
+        self.state().calls.len()
+    }
+
+    /// 呼び出しの記録を消去
+    pub fn clear_calls(&self) {
+        self.state().calls.clear();
+    }
+
+    /// 応答の設定と呼び出しの記録をすべて消去
+    pub fn reset(&self) {
+        *self.state() = MockState::default();
+    }
+}
+
+#[async_trait]
+impl AggregatorClientTrait for MockAggregatorClient {
+    async fn find_routers(&self, params: FindRouterParams) -> Result<Option<RouterData>> {
+        self.state().calls.push(MockCall {
+            params: params.clone(),
+            received_at: Instant::now(),
+        });
+
+        // 実際のクライアントと同様に不正なパラメータは送信前に拒否し、設定した応答を消費しない
+        params.validate_in(&ParamLimits::default(), global_locale())?;
+
+        let (latency, response) = {
+            let mut state = self.state();
+            (state.latency, state.next_response(&params))
+        };
+
+        if !latency.is_zero() {
+            tokio::time::sleep(latency).await;
+        }
+
+        let mut response = response.ok_or_else(|| {
+            AggregatorError::InputError(tr!(
+                "モックに応答が設定されていません: {} -> {} ({})",
+                "no mock response configured: {} -> {} ({})",
+                params.from,
+                params.target,
+                params.amount
+            ))
+        })?;
+
+        loop {
+            match response {
+                // 実際のクライアントと同様に`error`と`insufficient_liquidity`はエラーとして返す
+                MockResponse::Route(mut data) => {
+                    data.by_amount_in.get_or_insert(params.by_amount_in);
+                    return check_router_data(*data, || mock_context(&params)).map(Some);
+                }
+                MockResponse::NoRoute => return Ok(None),
+                MockResponse::Error(factory) => return Err(factory(&params)),
+                MockResponse::Delayed(delay, inner) => {
+                    tokio::time::sleep(delay).await;
+                    response = *inner;
+                }
+            }
+        }
+    }
+}
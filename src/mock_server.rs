@@ -0,0 +1,471 @@
+/**
+ * テスト用のローカルHTTPサーバー
+ *
+ * このモジュールは`test-util`フィーチャーで有効になり、
+ * `find_routes`のGET/POSTプロトコルを話すプロセス内のHTTPサーバーを提供します。
+ * パスは問わないため、`api_path`を変更したクライアントも検証できます。
+ * 応答は固定またはプログラム可能で、5xxエラー・遅延・不正なJSON・各サーバーエラーコードを再現できます。
+ * ネットワークに接続せずに[`AggregatorClient`](crate::client::AggregatorClient)をエンドツーエンドで検証できます。
+ */
+use std::collections::VecDeque;
+use std::fmt;
+use std::io;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+use reqwest::Url;
+use serde_json::{json, Value};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+
+use crate::client::AggregatorClient;
+use crate::error::AggregatorServerErrorCode;
+use crate::locale::Locale;
+use crate::models::{AggregatorResponse, RouterData};
+
+/// 受け付けるリクエストヘッダーの最大サイズ
+const MAX_HEADER_BYTES: usize = 64 * 1024;
+
+/// 応答を作成する関数
+type Responder = Arc<dyn Fn(&ReceivedRequest) -> ServerResponse + Send + Sync>;
+
+/// モックサーバーが返すHTTPレスポンス
+#[derive(Debug, Clone)]
+pub enum ServerResponse {
+    /// ステータスコード・ヘッダー・本文をそのまま返す
+    Raw {
+        /// HTTPステータスコード
+        status: u16,
+        /// 追加のレスポンスヘッダー
+        headers: Vec<(String, String)>,
+        /// レスポンス本文
+        body: String,
+    },
+    /// 指定した時間だけ待機してからレスポンスを返す
+    Delayed(Duration, Box<ServerResponse>),
+}
+
+impl ServerResponse {
+    /// 指定したステータスコードでJSONを返すレスポンスを作成
+    pub fn json(status: u16, body: &Value) -> Self {
+        Self::Raw {
+            status,
+            headers: vec![("Content-Type".to_string(), "application/json".to_string())],
+            body: body.to_string(),
+        }
+    }
+
+    /// `AggregatorResponse`をJSONとして返すレスポンスを作成（ステータスコード200）
+    pub fn response(response: &AggregatorResponse) -> Self {
+        Self::json(
+            200,
+            &serde_json::to_value(response).expect("AggregatorResponseはJSONに変換できる"),
+        )
+    }
+
+    /// ルート検索結果を返すレスポンスを作成
+    pub fn success(data: RouterData) -> Self {
+        Self::response(&AggregatorResponse {
+            code: 200,
+            msg: "Success".to_string(),
+            data: Some(data),
+        })
+    }
+
+    /// データを含まない成功レスポンスを作成（クライアントは`Ok(None)`を返します）
+    pub fn no_data() -> Self {
+        Self::response(&AggregatorResponse {
+            code: 200,
+            msg: "Success".to_string(),
+            data: None,
+        })
+    }
+
+    /// サーバーのエラーコードを返すレスポンスを作成
+    pub fn server_error(code: AggregatorServerErrorCode) -> Self {
+        Self::api_error(code as u32, code.message_in(Locale::English))
+    }
+
+    /// ルート検索結果の`error`フィールドでサーバーのエラーコードを返すレスポンスを作成
+    ///
+    /// サーバーは`code`を200のまま、`data.error`でエラーを返す場合があります。
+    pub fn router_error(code: AggregatorServerErrorCode) -> Self {
+        Self::json(
+            200,
+            &json!({
+                "code": 200,
+                "msg": "Success",
+                "data": {
+                    "amount_in": "0",
+                    "amount_out": "0",
+                    "routes": [],
+                    "error": { "code": code as u32, "msg": code.message_in(Locale::English) },
+                },
+            }),
+        )
+    }
+
+    /// 任意のエラーコードとメッセージを返すレスポンスを作成
+    pub fn api_error(code: u32, msg: impl Into<String>) -> Self {
+        Self::response(&AggregatorResponse {
+            code,
+            msg: msg.into(),
+            data: None,
+        })
+    }
+
+    /// 指定したステータスコードと本文を返すレスポンスを作成（5xxエラーなど）
+    pub fn status(status: u16, body: impl Into<String>) -> Self {
+        Self::Raw {
+            status,
+            headers: vec![("Content-Type".to_string(), "text/plain".to_string())],
+            body: body.into(),
+        }
+    }
+
+    /// 途中で切れた不正なJSONを返すレスポンスを作成
+    pub fn malformed_json() -> Self {
+        Self::Raw {
+            status: 200,
+            headers: vec![("Content-Type".to_string(), "application/json".to_string())],
+            body: r#"{"code":200,"msg":"Success","data":{"amount_in":"1","#.to_string(),
+        }
+    }
+
+    /// レスポンスヘッダーを追加
+    pub fn with_header(self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        match self {
+            Self::Raw {
+                status,
+                mut headers,
+                body,
+            } => {
+                headers.push((name.into(), value.into()));
+                Self::Raw {
+                    status,
+                    headers,
+                    body,
+                }
+            }
+            Self::Delayed(delay, response) => {
+                Self::Delayed(delay, Box::new(response.with_header(name, value)))
+            }
+        }
+    }
+
+    /// 指定した時間だけ待機してからこのレスポンスを返すように変換
+    pub fn delayed(self, delay: Duration) -> Self {
+        Self::Delayed(delay, Box::new(self))
+    }
+}
+
+/// モックサーバーが受信したリクエスト
+#[derive(Debug, Clone)]
+pub struct ReceivedRequest {
+    /// HTTPメソッド
+    pub method: String,
+    /// パス（クエリ文字列を除く）
+    pub path: String,
+    /// デコード済みのクエリパラメータ（受信順）
+    pub query: Vec<(String, String)>,
+    /// リクエストヘッダー（名前は小文字）
+    pub headers: Vec<(String, String)>,
+    /// リクエスト本文
+    pub body: Vec<u8>,
+}
+
+impl ReceivedRequest {
+    /// クエリパラメータの値を取得
+    pub fn query_param(&self, name: &str) -> Option<&str> {
+        self.query
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// リクエストヘッダーの値を取得（名前の大文字・小文字は区別しない）
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// 本文をJSONとして解析
+    pub fn body_json(&self) -> Option<Value> {
+        serde_json::from_slice(&self.body).ok()
+    }
+
+    /// GETのクエリパラメータまたはPOSTの本文から、パラメータの値を文字列として取得
+    pub fn param(&self, name: &str) -> Option<String> {
+        if let Some(value) = self.query_param(name) {
+            return Some(value.to_string());
+        }
+        match self.body_json()?.get(name)? {
+            Value::String(s) => Some(s.clone()),
+            Value::Null => None,
+            value => Some(value.to_string()),
+        }
+    }
+}
+
+#[derive(Default)]
+struct ServerState {
+    /// 順に1回ずつ返すレスポンス
+    queue: VecDeque<ServerResponse>,
+    /// キューが空の場合にレスポンスを作成する関数
+    responder: Option<Responder>,
+    requests: Vec<ReceivedRequest>,
+}
+
+impl ServerState {
+    fn next_response(&mut self, request: &ReceivedRequest) -> ServerResponse {
+        if let Some(response) = self.queue.pop_front() {
+            return response;
+        }
+        match &self.responder {
+            Some(responder) => responder(request),
+            None => ServerResponse::status(404, "no mock response configured"),
+        }
+    }
+}
+
+/// `find_routes`のGET/POSTに応答するローカルHTTPサーバー
+///
+/// ループバックアドレスの空きポートで待ち受け、ドロップすると停止します。
+/// リクエストのパスは問わずに応答するため、受信したパスは[`ReceivedRequest::path`]で検証してください。
+///
+/// ```
+/// use cetus_aggregator_rust::fixture::RouterDataBuilder;
+/// use cetus_aggregator_rust::mock_server::{MockServer, ServerResponse};
+/// use cetus_aggregator_rust::{AggregatorClientTrait, CoinType, FindRouterParams};
+///
+/// # tokio_test::block_on(async {
+/// let sui = CoinType::sui();
+/// let usdc: CoinType = "0x5d4b302506645c37ff133b98c4b50a5ae14841659738d6d733d59d0d217a93bf::coin::COIN".parse().unwrap();
+///
+/// let server = MockServer::start().await.unwrap();
+/// server.respond(ServerResponse::success(
+///     RouterDataBuilder::swap(sui.clone(), usdc.clone(), 1_000u64, 3u64).build(),
+/// ));
+///
+/// let params = FindRouterParams::builder().from(sui).target(usdc).amount(1_000u64).build().unwrap();
+/// let data = server.client().find_routers(params).await.unwrap().unwrap();
+/// assert_eq!(data.amount_out, 3u64.into());
+/// assert_eq!(server.requests()[0].param("amount").as_deref(), Some("1000"));
+/// # });
+/// ```
+pub struct MockServer {
+    addr: SocketAddr,
+    state: Arc<Mutex<ServerState>>,
+    task: JoinHandle<()>,
+}
+
+impl MockServer {
+    /// サーバーを起動
+    pub async fn start() -> io::Result<Self> {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).await?;
+        let addr = listener.local_addr()?;
+        let state = Arc::new(Mutex::new(ServerState::default()));
+
+        let task = tokio::spawn({
+            let state = Arc::clone(&state);
+            async move {
+                while let Ok((stream, _)) = listener.accept().await {
+                    tokio::spawn(handle_connection(stream, Arc::clone(&state)));
+                }
+            }
+        });
+
+        Ok(Self { addr, state, task })
+    }
+
+    fn state(&self) -> MutexGuard<'_, ServerState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// 待ち受けているアドレス
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// クライアントのエンドポイントとして使用するURL
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// このサーバーに接続するクライアントを作成
+    pub fn client(&self) -> AggregatorClient {
+        AggregatorClient::new(Some(self.url()))
+    }
+
+    /// すべてのリクエストに同じレスポンスを返すように設定
+    pub fn respond(&self, response: ServerResponse) -> &Self {
+        self.respond_with(move |_| response.clone())
+    }
+
+    /// リクエストからレスポンスを作成する関数を設定
+    pub fn respond_with<F>(&self, responder: F) -> &Self
+    where
+        F: Fn(&ReceivedRequest) -> ServerResponse + Send + Sync + 'static,
+    {
+        self.state().responder = Some(Arc::new(responder));
+        self
+    }
+
+    /// 次のリクエストに1回だけ返すレスポンスを追加
+    ///
+    /// 追加したレスポンスは追加した順に返し、なくなると`respond`・`respond_with`の設定に戻ります。
+    pub fn enqueue(&self, response: ServerResponse) -> &Self {
+        self.state().queue.push_back(response);
+        self
+    }
+
+    /// 受信したリクエストの一覧（受信順）
+    pub fn requests(&self) -> Vec<ReceivedRequest> {
+        self.state().requests.clone()
+    }
+
+    /// 受信したリクエストの数
+    pub fn request_count(&self) -> usize {
+        self.state().requests.len()
+    }
+
+    /// 設定と受信したリクエストの記録をすべて消去
+    pub fn reset(&self) {
+        *self.state() = ServerState::default();
+    }
+}
+
+impl fmt::Debug for MockServer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MockServer")
+            .field("addr", &self.addr)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// 1つの接続で1つのリクエストを処理して接続を閉じる
+async fn handle_connection(mut stream: TcpStream, state: Arc<Mutex<ServerState>>) {
+    let Ok(Some(request)) = read_request(&mut stream).await else {
+        return;
+    };
+
+    let mut response = {
+        let mut state = state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        state.requests.push(request.clone());
+        state.next_response(&request)
+    };
+
+    loop {
+        match response {
+            ServerResponse::Delayed(delay, inner) => {
+                tokio::time::sleep(delay).await;
+                response = *inner;
+            }
+            ServerResponse::Raw {
+                status,
+                headers,
+                body,
+            } => {
+                let mut head = format!(
+                    "HTTP/1.1 {} {}\r\nContent-Length: {}\r\nConnection: close\r\n",
+                    status,
+                    reason_phrase(status),
+                    body.len()
+                );
+                for (name, value) in headers {
+                    head.push_str(&format!("{}: {}\r\n", name, value));
+                }
+                head.push_str("\r\n");
+
+                let _ = stream.write_all(head.as_bytes()).await;
+                let _ = stream.write_all(body.as_bytes()).await;
+                let _ = stream.shutdown().await;
+                return;
+            }
+        }
+    }
+}
+
+/// リクエストを読み込む（接続が閉じられた場合は`None`）
+async fn read_request(stream: &mut TcpStream) -> io::Result<Option<ReceivedRequest>> {
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 4096];
+
+    let header_end = loop {
+        if let Some(position) = buffer.windows(4).position(|window| window == b"\r\n\r\n") {
+            break position;
+        }
+        if buffer.len() > MAX_HEADER_BYTES {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "header too large",
+            ));
+        }
+        let read = stream.read(&mut chunk).await?;
+        if read == 0 {
+            return Ok(None);
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+    };
+
+    let head = String::from_utf8_lossy(&buffer[..header_end]).into_owned();
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next().unwrap_or_default().split_whitespace();
+    let method = request_line.next().unwrap_or_default().to_string();
+    let target = request_line.next().unwrap_or("/");
+
+    let headers: Vec<(String, String)> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_ascii_lowercase(), value.trim().to_string()))
+        .collect();
+    let content_length = headers
+        .iter()
+        .find(|(name, _)| name == "content-length")
+        .and_then(|(_, value)| value.parse::<usize>().ok())
+        .unwrap_or(0);
+
+    let mut body = buffer.split_off(header_end + 4);
+    while body.len() < content_length {
+        let read = stream.read(&mut chunk).await?;
+        if read == 0 {
+            break;
+        }
+        body.extend_from_slice(&chunk[..read]);
+    }
+    body.truncate(content_length);
+
+    // パスとクエリのデコードはURLのパーサーに任せる
+    let url = Url::parse("http://localhost")
+        .and_then(|base| base.join(target))
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+    Ok(Some(ReceivedRequest {
+        method,
+        path: url.path().to_string(),
+        query: url.query_pairs().into_owned().collect(),
+        headers,
+        body,
+    }))
+}
+
+/// ステータスコードの理由句
+fn reason_phrase(status: u16) -> &'static str {
+    reqwest::StatusCode::from_u16(status)
+        .ok()
+        .and_then(|status| status.canonical_reason())
+        .unwrap_or("Unknown")
+}
@@ -3,17 +3,44 @@
  *
  * このモジュールはAPIとの通信に使用するデータ構造を定義します。
  */
+use std::collections::HashSet;
+
 use primitive_types::U256;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use serde_json::{Map, Value};
+
+use crate::amount::{deserialize_u256, serialize_u256};
+use crate::coin::CoinType;
+use crate::details::ExtendedDetails;
+use crate::error::{AggregatorError, Result};
+use crate::locale::{global_locale, tr, Locale};
+use crate::package::{PackageMap, PackageViolation};
+use crate::provider::{Provider, ProviderFilter};
+use crate::ratio::Ratio;
+use crate::split::SplitAlgorithm;
+
+/// 検索の深さの最小値
+pub const MIN_DEPTH: u32 = 1;
+
+/// パラメータの検証で使用する上限
+///
+/// APIの公開仕様は深さと分割数の上限を定義していないため、デフォルトでは上限を設けず、
+/// 範囲外の値はサーバーの判断に任せます。送信前に拒否したい場合にのみ上限を設定してください。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ParamLimits {
+    /// 検索の深さの最大値（`None`の場合は上限なし）
+    pub max_depth: Option<u32>,
+    /// 分割数の最大値（`None`の場合は上限なし）
+    pub max_split_count: Option<u32>,
+}
 
 /// ルート検索のためのパラメータ
 #[derive(Debug, Serialize, Clone)]
 pub struct FindRouterParams {
-    /// 交換元コインのアドレス
-    pub from: String,
-    /// 交換先コインのアドレス
-    pub target: String,
+    /// 交換元コインのタイプ
+    pub from: CoinType,
+    /// 交換先コインのタイプ
+    pub target: CoinType,
     /// 交換する金額
     #[serde(serialize_with = "serialize_u256")]
     pub amount: U256,
@@ -23,48 +50,265 @@ pub struct FindRouterParams {
     /// 検索の深さ（オプション）
     #[serde(skip_serializing_if = "Option::is_none")]
     pub depth: Option<u32>,
-    /// 分割アルゴリズム（オプション）
-    #[serde(rename = "split_algorithm", skip_serializing_if = "Option::is_none")]
-    pub split_algorithm: Option<String>,
-    /// 分割係数（オプション）
-    #[serde(rename = "split_factor", skip_serializing_if = "Option::is_none")]
-    pub split_factor: Option<f64>,
-    /// 分割数（オプション）
-    #[serde(rename = "split_count", skip_serializing_if = "Option::is_none")]
-    pub split_count: Option<u32>,
-    /// 使用するプロバイダーのリスト（オプション）
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub providers: Option<Vec<String>>,
+    /// 取引の分割方法（オプション）
+    #[serde(flatten, skip_serializing_if = "Option::is_none")]
+    pub split: Option<SplitAlgorithm>,
+    /// 使用するプロバイダーの指定（デフォルトはすべてのプロバイダー）
+    #[serde(skip_serializing_if = "ProviderFilter::is_all")]
+    pub providers: ProviderFilter,
     /// 流動性変更のシミュレーション（オプション）
     #[serde(rename = "liquidity_changes", skip_serializing_if = "Option::is_none")]
     pub liquidity_changes: Option<Vec<PreSwapLpChangeParams>>,
 }
 
-/// U256をシリアライズするためのヘルパー関数
-fn serialize_u256<S>(value: &U256, serializer: S) -> Result<S::Ok, S::Error>
-where
-    S: serde::Serializer,
-{
-    serializer.serialize_str(&value.to_string())
-}
-
+/// `from`と`target`はSUIが初期値になるため、必ず指定してください。
 impl Default for FindRouterParams {
     fn default() -> Self {
         Self {
-            from: String::new(),
-            target: String::new(),
+            from: CoinType::sui(),
+            target: CoinType::sui(),
             amount: U256::zero(),
             by_amount_in: true,
             depth: None,
-            split_algorithm: None,
-            split_factor: None,
-            split_count: None,
-            providers: None,
+            split: None,
+            providers: ProviderFilter::All,
             liquidity_changes: None,
         }
     }
 }
 
+impl FindRouterParams {
+    /// パラメータのビルダーを作成
+    ///
+    /// ビルダーは[`build`](FindRouterParamsBuilder::build)の時点でパラメータを検証します。
+    pub fn builder() -> FindRouterParamsBuilder {
+        FindRouterParamsBuilder::default()
+    }
+
+    /// パラメータを検証
+    ///
+    /// 金額が0、交換元と交換先が同じ、深さ・分割数が1未満、
+    /// 分割係数が不正、使用するプロバイダーが1つもない場合は、
+    /// すべての理由をまとめた`InputError`を返します。
+    pub fn validate(&self) -> Result<()> {
+        self.validate_with(&ParamLimits::default())
+    }
+
+    /// 上限を指定してパラメータを検証
+    pub fn validate_with(&self, limits: &ParamLimits) -> Result<()> {
+        self.validate_in(limits, global_locale())
+    }
+
+    /// 指定した上限とロケールのメッセージでパラメータを検証
+    pub(crate) fn validate_in(&self, limits: &ParamLimits, locale: Locale) -> Result<()> {
+        let mut errors = Vec::new();
+
+        if self.amount.is_zero() {
+            errors.push(tr!(
+                locale =>
+                "金額は0より大きい値を指定してください",
+                "amount must be greater than 0"
+            ));
+        }
+
+        if self.from == self.target {
+            errors.push(tr!(
+                locale =>
+                "交換元と交換先に同じコインタイプは指定できません: {}",
+                "from and target must be different coin types: {}",
+                self.from.to_short_string()
+            ));
+        }
+
+        if let Some(depth) = self.depth {
+            match limits.max_depth {
+                Some(max) if !(MIN_DEPTH..=max).contains(&depth) => errors.push(tr!(
+                    locale =>
+                    "depthは{}以上{}以下で指定してください: {}",
+                    "depth must be between {} and {}: {}",
+                    MIN_DEPTH,
+                    max,
+                    depth
+                )),
+                None if depth < MIN_DEPTH => errors.push(tr!(
+                    locale =>
+                    "depthは{}以上で指定してください: {}",
+                    "depth must be at least {}: {}",
+                    MIN_DEPTH,
+                    depth
+                )),
+                _ => {}
+            }
+        }
+
+        if let Some(split) = &self.split {
+            errors.extend(split.violations(limits.max_split_count, locale));
+        }
+
+        if self
+            .providers
+            .resolve()
+            .is_some_and(|providers| providers.is_empty())
+        {
+            errors.push(tr!(
+                locale =>
+                "使用するプロバイダーが1つもありません",
+                "no providers are selected"
+            ));
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(AggregatorError::InputError(errors.join(", ")))
+        }
+    }
+}
+
+/// [`FindRouterParams`]のビルダー
+///
+/// `from`・`target`・`amount`は必須です。
+/// 不正な値は[`build`](Self::build)でまとめて`InputError`として返されます。
+#[derive(Debug, Default)]
+pub struct FindRouterParamsBuilder {
+    from: Option<CoinType>,
+    target: Option<CoinType>,
+    amount: Option<U256>,
+    by_amount_in: Option<bool>,
+    depth: Option<u32>,
+    split: Option<SplitAlgorithm>,
+    providers: ProviderFilter,
+    liquidity_changes: Option<Vec<PreSwapLpChangeParams>>,
+    limits: ParamLimits,
+    errors: Vec<String>,
+    /// 解析に失敗した（エラーを記録済みの）フィールド名
+    parse_failed: HashSet<&'static str>,
+}
+
+impl FindRouterParamsBuilder {
+    /// 交換元コインのタイプを設定
+    ///
+    /// 文字列の場合は解析し、不正な場合は[`build`](Self::build)でエラーになります。
+    pub fn from<C>(mut self, coin: C) -> Self
+    where
+        C: TryInto<CoinType>,
+        C::Error: Into<AggregatorError>,
+    {
+        self.from = self.parse_coin("from", coin);
+        self
+    }
+
+    /// 交換先コインのタイプを設定
+    ///
+    /// 文字列の場合は解析し、不正な場合は[`build`](Self::build)でエラーになります。
+    pub fn target<C>(mut self, coin: C) -> Self
+    where
+        C: TryInto<CoinType>,
+        C::Error: Into<AggregatorError>,
+    {
+        self.target = self.parse_coin("target", coin);
+        self
+    }
+
+    /// 交換する金額を設定
+    pub fn amount(mut self, amount: impl Into<U256>) -> Self {
+        self.amount = Some(amount.into());
+        self
+    }
+
+    /// 入力量ベースで計算するかどうかを設定（デフォルトは`true`）
+    pub fn by_amount_in(mut self, by_amount_in: bool) -> Self {
+        self.by_amount_in = Some(by_amount_in);
+        self
+    }
+
+    /// 検索の深さを設定
+    pub fn depth(mut self, depth: u32) -> Self {
+        self.depth = Some(depth);
+        self
+    }
+
+    /// 取引の分割方法を設定
+    pub fn split(mut self, split: SplitAlgorithm) -> Self {
+        self.split = Some(split);
+        self
+    }
+
+    /// 使用するプロバイダーを設定
+    pub fn providers(mut self, providers: impl Into<ProviderFilter>) -> Self {
+        self.providers = providers.into();
+        self
+    }
+
+    /// 流動性変更のシミュレーションを追加
+    pub fn liquidity_change(mut self, change: PreSwapLpChangeParams) -> Self {
+        self.liquidity_changes
+            .get_or_insert_with(Vec::new)
+            .push(change);
+        self
+    }
+
+    /// 検証に使用する深さ・分割数の上限を設定（デフォルトは上限なし）
+    ///
+    /// クライアントで送信前にも同じ上限を適用する場合は、
+    /// [`AggregatorClientBuilder::param_limits`](crate::client::AggregatorClientBuilder::param_limits)にも設定してください。
+    pub fn limits(mut self, limits: ParamLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// パラメータを構築
+    ///
+    /// # 戻り値
+    ///
+    /// 成功した場合は検証済みのFindRouterParams、値が不足・不正な場合は`InputError`を返します。
+    pub fn build(mut self) -> Result<FindRouterParams> {
+        for (name, missing) in [
+            ("from", self.from.is_none()),
+            ("target", self.target.is_none()),
+            ("amount", self.amount.is_none()),
+        ] {
+            // 解析に失敗した値は既にエラーとして記録済み
+            if missing && !self.parse_failed.contains(name) {
+                self.errors
+                    .push(tr!("{}が指定されていません", "{} is required", name));
+            }
+        }
+        if !self.errors.is_empty() {
+            return Err(AggregatorError::InputError(self.errors.join(", ")));
+        }
+
+        let params = FindRouterParams {
+            from: self.from.unwrap_or_else(CoinType::sui),
+            target: self.target.unwrap_or_else(CoinType::sui),
+            amount: self.amount.unwrap_or_default(),
+            by_amount_in: self.by_amount_in.unwrap_or(true),
+            depth: self.depth,
+            split: self.split,
+            providers: self.providers,
+            liquidity_changes: self.liquidity_changes,
+        };
+        params.validate_with(&self.limits)?;
+        Ok(params)
+    }
+
+    /// コインタイプに変換し、失敗した場合はエラーを記録
+    fn parse_coin<C>(&mut self, name: &'static str, coin: C) -> Option<CoinType>
+    where
+        C: TryInto<CoinType>,
+        C::Error: Into<AggregatorError>,
+    {
+        let reason = match coin.try_into().map_err(Into::into) {
+            Ok(coin) => return Some(coin),
+            Err(AggregatorError::InputError(reason)) => reason,
+            Err(e) => e.to_string(),
+        };
+        self.errors.push(format!("{}: {}", name, reason));
+        self.parse_failed.insert(name);
+        None
+    }
+}
+
 /// 流動性変更のパラメータ
 #[derive(Debug, Serialize, Clone)]
 pub struct PreSwapLpChangeParams {
@@ -83,88 +327,101 @@ pub struct PreSwapLpChangeParams {
 }
 
 /// パス情報
-#[derive(Debug, Deserialize, Clone)]
+///
+/// `extended_details`は`provider`に応じた型付きの詳細情報に変換されます。
+/// 必須フィールドが不足している場合や型が不正な場合も受信したフィールドを保持し、
+/// [`RouterData::validate`]で違反として報告します。
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(from = "RawPath")]
 pub struct Path {
     /// パスのID
     pub id: String,
     /// 方向（真の場合A→B、偽の場合B→A）
     pub direction: bool,
-    /// プロバイダー名
-    pub provider: String,
+    /// プロバイダー
+    pub provider: Provider,
     /// 交換元コイン
-    pub from: String,
+    pub from: CoinType,
     /// 交換先コイン
-    pub target: String,
-    /// 手数料率
-    #[serde(rename = "fee_rate")]
-    pub fee_rate: String,
+    pub target: CoinType,
+    /// 手数料率（入力量に対する割合、例: `0.0025` = 0.25%）
+    pub fee_rate: Ratio,
     /// 入力量
-    #[serde(rename = "amount_in")]
-    pub amount_in: u64,
+    #[serde(serialize_with = "serialize_u256")]
+    pub amount_in: U256,
     /// 出力量
-    #[serde(rename = "amount_out")]
-    pub amount_out: u64,
+    #[serde(serialize_with = "serialize_u256")]
+    pub amount_out: U256,
     /// バージョン（オプション）
     pub version: Option<String>,
     /// 拡張詳細情報（オプション）
-    #[serde(rename = "extended_details")]
     pub extended_details: Option<ExtendedDetails>,
 }
 
-/// 拡張詳細情報
-#[derive(Debug, Deserialize, Clone)]
-pub struct ExtendedDetails {
-    // 各プロバイダー固有の追加情報
-    #[serde(rename = "aftermath_pool_flatness")]
-    pub aftermath_pool_flatness: Option<f64>,
-    #[serde(rename = "aftermath_lp_supply_type")]
-    pub aftermath_lp_supply_type: Option<String>,
-    #[serde(rename = "turbos_fee_type")]
-    pub turbos_fee_type: Option<String>,
-    #[serde(rename = "after_sqrt_price")]
-    pub after_sqrt_price: Option<u128>,
-    #[serde(rename = "deepbookv3_deep_fee")]
-    pub deepbookv3_deep_fee: Option<f64>,
-    #[serde(rename = "scallop_scoin_treasury")]
-    pub scallop_scoin_treasury: Option<String>,
-    #[serde(rename = "haedal_pmm_base_price_seed")]
-    pub haedal_pmm_base_price_seed: Option<String>,
-    #[serde(rename = "haedal_pmm_quote_price_seed")]
-    pub haedal_pmm_quote_price_seed: Option<String>,
-    #[serde(rename = "steamm_bank_a")]
-    pub steamm_bank_a: Option<String>,
-    #[serde(rename = "steamm_bank_b")]
-    pub steamm_bank_b: Option<String>,
-    #[serde(rename = "steamm_lending_market")]
-    pub steamm_lending_market: Option<String>,
-    #[serde(rename = "steamm_lending_market_type")]
-    pub steamm_lending_market_type: Option<String>,
-    #[serde(rename = "steamm_btoken_a_type")]
-    pub steamm_btoken_a_type: Option<String>,
-    #[serde(rename = "steamm_btoken_b_type")]
-    pub steamm_btoken_b_type: Option<String>,
-    #[serde(rename = "steamm_lp_token_type")]
-    pub steamm_lp_token_type: Option<String>,
+/// APIから受信したままのパス情報
+#[derive(Deserialize)]
+struct RawPath {
+    id: String,
+    direction: bool,
+    provider: Provider,
+    from: CoinType,
+    target: CoinType,
+    #[serde(rename = "fee_rate")]
+    fee_rate: Ratio,
+    #[serde(rename = "amount_in", deserialize_with = "deserialize_u256")]
+    amount_in: U256,
+    #[serde(rename = "amount_out", deserialize_with = "deserialize_u256")]
+    amount_out: U256,
+    version: Option<String>,
+    #[serde(rename = "extended_details")]
+    extended_details: Option<Map<String, Value>>,
+}
+
+impl From<RawPath> for Path {
+    fn from(raw: RawPath) -> Self {
+        let extended_details = ExtendedDetails::from_raw_lossy(&raw.provider, raw.extended_details);
+
+        Self {
+            id: raw.id,
+            direction: raw.direction,
+            provider: raw.provider,
+            from: raw.from,
+            target: raw.target,
+            fee_rate: raw.fee_rate,
+            amount_in: raw.amount_in,
+            amount_out: raw.amount_out,
+            version: raw.version,
+            extended_details,
+        }
+    }
 }
 
 /// ルーター情報
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Router {
     /// パスのリスト
     pub path: Vec<Path>,
     /// 入力量
-    #[serde(rename = "amount_in")]
-    pub amount_in: u64,
+    #[serde(
+        rename = "amount_in",
+        serialize_with = "serialize_u256",
+        deserialize_with = "deserialize_u256"
+    )]
+    pub amount_in: U256,
     /// 出力量
-    #[serde(rename = "amount_out")]
-    pub amount_out: u64,
-    /// 初期価格
+    #[serde(
+        rename = "amount_out",
+        serialize_with = "serialize_u256",
+        deserialize_with = "deserialize_u256"
+    )]
+    pub amount_out: U256,
+    /// 初期価格（取引前の最小単位あたりの出力量）
     #[serde(rename = "initial_price")]
-    pub initial_price: String,
+    pub initial_price: Ratio,
 }
 
 /// エラー情報
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct RouterError {
     /// エラーコード
     pub code: u32,
@@ -173,33 +430,53 @@ pub struct RouterError {
 }
 
 /// ルーター検索結果データ
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct RouterData {
     /// 入力量
-    #[serde(rename = "amount_in")]
-    pub amount_in: u64,
+    #[serde(
+        rename = "amount_in",
+        serialize_with = "serialize_u256",
+        deserialize_with = "deserialize_u256"
+    )]
+    pub amount_in: U256,
     /// 出力量
-    #[serde(rename = "amount_out")]
-    pub amount_out: u64,
+    #[serde(
+        rename = "amount_out",
+        serialize_with = "serialize_u256",
+        deserialize_with = "deserialize_u256"
+    )]
+    pub amount_out: U256,
     /// 入力量ベースフラグ
-    #[serde(rename = "by_amount_in", default)]
-    pub by_amount_in: bool,
+    ///
+    /// サーバーは省略することが多いため、含まれない場合はクライアントがリクエストの値で補完します。
+    /// 補完されていないデータで`None`の場合、[`validate`](RouterData::validate)は違反として報告します。
+    #[serde(
+        rename = "by_amount_in",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub by_amount_in: Option<bool>,
     /// ルーターのリスト
     pub routes: Vec<Router>,
     /// 流動性不足フラグ
     #[serde(rename = "insufficient_liquidity", default)]
     pub insufficient_liquidity: bool,
     /// パッケージマップ（オプション）
-    pub packages: Option<HashMap<String, String>>,
+    pub packages: Option<PackageMap>,
     /// 深層手数料総額（オプション）
     #[serde(rename = "total_deep_fee")]
     pub total_deep_fee: Option<f64>,
     /// エラー情報（オプション）
     pub error: Option<RouterError>,
+    /// 許可リストと照合して検出されたパッケージの違反
+    ///
+    /// クライアントのパッケージ検証が[`PackagePolicy::Flag`](crate::package::PackagePolicy::Flag)の場合に設定されます。
+    #[serde(skip)]
+    pub package_violations: Vec<PackageViolation>,
 }
 
 /// アグリゲーターレスポンス
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct AggregatorResponse {
     /// ステータスコード
     pub code: u32,
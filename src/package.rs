@@ -0,0 +1,404 @@
+/**
+ * パッケージの許可リスト
+ *
+ * このモジュールは`RouterData.packages`に含まれるパッケージIDを、
+ * ネットワーク・パッケージ名ごとに固定（ピン留め）したIDと照合する仕組みを定義します。
+ * 想定外のコントラクトへルーティングされることを防ぐために使用します。
+ *
+ * 検証の対象範囲は次のとおりです。
+ *
+ * * 既知のパッケージIDは同梱していないため、許可リストを設定しない限り何も検証しません
+ * * 許可リストはネットワークとパッケージ名で区別し、プロバイダーごとには区別しません
+ *   （レスポンスの`packages`にプロバイダーの情報が含まれないため）
+ * * 許可リストを設定した場合、登録していないパッケージ名や固定していないIDはすべて違反になります
+ */
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use thiserror::Error;
+
+use crate::coin::normalize_address;
+use crate::error::{AggregatorError, Result};
+use crate::locale::{global_locale, tr, Locale};
+
+/// パッケージID
+///
+/// アドレスは`0x`と64桁の小文字16進数に正規化して保持します。
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PackageId(String);
+
+impl PackageId {
+    /// 正規化されたアドレス
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for PackageId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl FromStr for PackageId {
+    type Err = AggregatorError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        normalize_address(s).map(Self).ok_or_else(|| {
+            AggregatorError::InputError(tr!(
+                "不正なパッケージIDです: {}",
+                "invalid package ID: {}",
+                s
+            ))
+        })
+    }
+}
+
+impl Serialize for PackageId {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for PackageId {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// レスポンスに含まれるパッケージマップ（パッケージ名 → パッケージID）
+///
+/// アドレスとして解析できない値もレスポンス全体を失敗させずに元の文字列のまま保持し、
+/// [`PackageAllowlist::verify`]で違反として報告します。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PackageMap {
+    packages: HashMap<String, PackageId>,
+    invalid: HashMap<String, String>,
+}
+
+impl PackageMap {
+    /// パッケージ名に対応するパッケージIDを取得
+    pub fn get(&self, name: &str) -> Option<&PackageId> {
+        self.packages.get(name)
+    }
+
+    /// 正しい形式のパッケージをすべて取得
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &PackageId)> {
+        self.packages.iter()
+    }
+
+    /// アドレスとして解析できなかったパッケージ（パッケージ名, 受信した値）をすべて取得
+    pub fn invalid(&self) -> impl Iterator<Item = (&String, &String)> {
+        self.invalid.iter()
+    }
+
+    /// パッケージ数（解析できなかったものを含む）
+    pub fn len(&self) -> usize {
+        self.packages.len() + self.invalid.len()
+    }
+
+    /// パッケージが含まれていないかどうか
+    pub fn is_empty(&self) -> bool {
+        self.packages.is_empty() && self.invalid.is_empty()
+    }
+}
+
+impl FromIterator<(String, PackageId)> for PackageMap {
+    fn from_iter<I: IntoIterator<Item = (String, PackageId)>>(iter: I) -> Self {
+        Self {
+            packages: iter.into_iter().collect(),
+            invalid: HashMap::new(),
+        }
+    }
+}
+
+impl Serialize for PackageMap {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_map(
+            self.packages
+                .iter()
+                .map(|(name, package)| (name, package.as_str()))
+                .chain(self.invalid.iter().map(|(name, raw)| (name, raw.as_str()))),
+        )
+    }
+}
+
+impl<'de> Deserialize<'de> for PackageMap {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let raw = HashMap::<String, String>::deserialize(deserializer)?;
+        let mut map = Self::default();
+        for (name, value) in raw {
+            match value.parse() {
+                Ok(package) => {
+                    map.packages.insert(name, package);
+                }
+                Err(_) => {
+                    map.invalid.insert(name, value);
+                }
+            }
+        }
+        Ok(map)
+    }
+}
+
+/// Suiのネットワーク
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Network {
+    /// メインネット
+    Mainnet,
+    /// テストネット
+    Testnet,
+}
+
+/// 許可リストにないパッケージを検出した場合の扱い
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PackagePolicy {
+    /// レスポンスをエラーとして拒否する
+    #[default]
+    Reject,
+    /// レスポンスは返し、違反を`RouterData.package_violations`に記録する
+    Flag,
+}
+
+/// パッケージの検証で検出された違反
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum PackageViolation {
+    /// 許可リストに登録されていないパッケージ名
+    UnknownPackage {
+        /// パッケージ名
+        name: String,
+        /// パッケージID
+        package: PackageId,
+    },
+
+    /// パッケージ名は登録されているが、IDが固定されたものと一致しない
+    UnpinnedPackage {
+        /// パッケージ名
+        name: String,
+        /// パッケージID
+        package: PackageId,
+    },
+
+    /// パッケージIDがアドレスとして解析できない
+    InvalidPackageId {
+        /// パッケージ名
+        name: String,
+        /// 受信した値
+        value: String,
+    },
+}
+
+impl PackageViolation {
+    /// 指定したロケールで表示用の文字列に変換
+    pub fn to_localized_string(&self, locale: Locale) -> String {
+        match self {
+            Self::UnknownPackage { name, package } => tr!(
+                locale =>
+                "許可リストに登録されていないパッケージです: {} ({})",
+                "package is not in the allowlist: {} ({})",
+                name,
+                package
+            ),
+            Self::UnpinnedPackage { name, package } => tr!(
+                locale =>
+                "パッケージIDが固定されたIDと一致しません: {} ({})",
+                "package ID does not match the pinned IDs: {} ({})",
+                name,
+                package
+            ),
+            Self::InvalidPackageId { name, value } => tr!(
+                locale =>
+                "パッケージIDが不正です: {} ({})",
+                "package ID is invalid: {} ({})",
+                name,
+                value
+            ),
+        }
+    }
+}
+
+/// プロセス全体のロケールで表示
+impl fmt::Display for PackageViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_localized_string(global_locale()))
+    }
+}
+
+/// パッケージの許可リスト
+///
+/// ネットワークごとに、パッケージ名と許可するパッケージIDの組を保持します。
+/// レスポンスのパッケージマップはパッケージ名をキーとし、どのプロバイダーが使うかを含まないため、
+/// プロバイダーごとには区別しません。
+///
+/// パッケージIDはコントラクトのアップグレードで変わるため、このクレートには既知のIDを同梱していません。
+/// 公式のSDKやドキュメントで確認したIDを[`pin`](Self::pin)で指定するか、設定ファイルから読み込み、
+/// [`extend`](Self::extend)（追加）または[`override_with`](Self::override_with)（置き換え）で組み合わせてください。
+/// 設定ファイルは次の形式のJSONです。
+///
+/// ```json
+/// {
+///   "mainnet": {
+///     "aggregator_v2": ["0x..."],
+///     "aggregator_v2_extend": ["0x...", "0x..."]
+///   }
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct PackageAllowlist(HashMap<Network, HashMap<String, HashSet<PackageId>>>);
+
+impl PackageAllowlist {
+    /// 空の許可リストを作成
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// JSON文字列から許可リストを読み込む
+    pub fn from_json_str(json: &str) -> Result<Self> {
+        serde_json::from_str(json).map_err(|e| {
+            AggregatorError::InputError(tr!(
+                "許可リストの形式が不正です: {}",
+                "invalid allowlist format: {}",
+                e
+            ))
+        })
+    }
+
+    /// JSON設定ファイルから許可リストを読み込む
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let json = std::fs::read_to_string(path).map_err(|e| {
+            AggregatorError::InputError(tr!(
+                "設定ファイルを読み込めません ({}): {}",
+                "cannot read the configuration file ({}): {}",
+                path.display(),
+                e
+            ))
+        })?;
+        Self::from_json_str(&json)
+    }
+
+    /// パッケージIDを許可リストに追加
+    pub fn pin(mut self, network: Network, name: impl Into<String>, package: PackageId) -> Self {
+        self.0
+            .entry(network)
+            .or_default()
+            .entry(name.into())
+            .or_default()
+            .insert(package);
+        self
+    }
+
+    /// 別の許可リストのパッケージIDを追加
+    ///
+    /// 同じパッケージ名のIDはどちらも許可されます。
+    ///
+    /// ```
+    /// use cetus_aggregator_rust::PackageAllowlist;
+    ///
+    /// let base = PackageAllowlist::from_json_str(r#"{"mainnet": {"aggregator_v2": ["0x1"]}}"#)?;
+    /// let upgrade = PackageAllowlist::from_json_str(r#"{"mainnet": {"aggregator_v2": ["0x2"]}}"#)?;
+    /// let allowlist = base.extend(upgrade);
+    /// # Ok::<(), cetus_aggregator_rust::AggregatorError>(())
+    /// ```
+    pub fn extend(mut self, other: Self) -> Self {
+        for (network, names) in other.0 {
+            let pinned = self.0.entry(network).or_default();
+            for (name, packages) in names {
+                pinned.entry(name).or_default().extend(packages);
+            }
+        }
+        self
+    }
+
+    /// 別の許可リストに含まれるパッケージ名のIDを置き換える
+    ///
+    /// `other`に含まれないパッケージ名は元のIDのまま残ります。
+    pub fn override_with(mut self, other: Self) -> Self {
+        for (network, names) in other.0 {
+            self.0.entry(network).or_default().extend(names);
+        }
+        self
+    }
+
+    /// パッケージIDが許可されているかどうか
+    pub fn is_pinned(&self, network: Network, name: &str, package: &PackageId) -> bool {
+        self.0
+            .get(&network)
+            .and_then(|names| names.get(name))
+            .is_some_and(|packages| packages.contains(package))
+    }
+
+    /// パッケージマップを許可リストと照合
+    ///
+    /// # 引数
+    ///
+    /// * `network` - レスポンスを取得したネットワーク
+    /// * `packages` - レスポンスに含まれるパッケージマップ
+    ///
+    /// # 戻り値
+    ///
+    /// 許可されていない、または解析できなかったパッケージの一覧（パッケージ名順）
+    pub fn verify(&self, network: Network, packages: &PackageMap) -> Vec<PackageViolation> {
+        let pinned = self.0.get(&network);
+        let mut violations: Vec<PackageViolation> = packages
+            .invalid()
+            .map(|(name, value)| PackageViolation::InvalidPackageId {
+                name: name.clone(),
+                value: value.clone(),
+            })
+            .collect();
+        violations.extend(packages.iter().filter_map(|(name, package)| {
+            match pinned.and_then(|names| names.get(name)) {
+                None => Some(PackageViolation::UnknownPackage {
+                    name: name.clone(),
+                    package: package.clone(),
+                }),
+                Some(ids) if !ids.contains(package) => Some(PackageViolation::UnpinnedPackage {
+                    name: name.clone(),
+                    package: package.clone(),
+                }),
+                Some(_) => None,
+            }
+        }));
+
+        violations.sort_by(|a, b| violation_name(a).cmp(violation_name(b)));
+        violations
+    }
+}
+
+fn violation_name(violation: &PackageViolation) -> &str {
+    match violation {
+        PackageViolation::UnknownPackage { name, .. }
+        | PackageViolation::UnpinnedPackage { name, .. }
+        | PackageViolation::InvalidPackageId { name, .. } => name,
+    }
+}
+
+/// クライアントで使用するパッケージ検証の設定
+#[derive(Debug, Clone)]
+pub struct PackageVerification {
+    /// 許可リスト
+    pub allowlist: PackageAllowlist,
+    /// 接続先のネットワーク
+    pub network: Network,
+    /// 違反を検出した場合の扱い
+    pub policy: PackagePolicy,
+}
@@ -0,0 +1,241 @@
+/**
+ * プロバイダーの定義
+ *
+ * このモジュールはアグリゲーターが対応するDEX・プロトコル（プロバイダー）の列挙型と、
+ * ルート検索で使用するプロバイダーの指定方法を定義します。
+ */
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// プロバイダー
+///
+/// APIとの間では大文字のプロバイダー名（`CETUS`、`DEEPBOOKV3`など）で送受信します。
+/// このクレートが認識しないプロバイダーは[`Provider::Unknown`]として保持します。
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Provider {
+    /// Cetus
+    Cetus,
+    /// DeepBook v2
+    DeepbookV2,
+    /// DeepBook v3
+    DeepbookV3,
+    /// Kriya
+    Kriya,
+    /// Kriya v3
+    KriyaV3,
+    /// FlowX v2
+    FlowxV2,
+    /// FlowX v3
+    FlowxV3,
+    /// Turbos
+    Turbos,
+    /// Aftermath
+    Aftermath,
+    /// Haedal
+    Haedal,
+    /// Haedal PMM
+    HaedalPmm,
+    /// Volo
+    Volo,
+    /// afSUI
+    Afsui,
+    /// BlueMove
+    Bluemove,
+    /// Scallop
+    Scallop,
+    /// Suilend
+    Suilend,
+    /// Bluefin
+    Bluefin,
+    /// AlphaFi
+    Alphafi,
+    /// SpringSui
+    Springsui,
+    /// Steamm
+    Steamm,
+    /// Metastable
+    Metastable,
+    /// Obric
+    Obric,
+    /// このクレートが認識しないプロバイダー
+    Unknown(String),
+}
+
+impl Provider {
+    /// このクレートが認識しているすべてのプロバイダー
+    pub const ALL: &'static [Provider] = &[
+        Provider::Cetus,
+        Provider::DeepbookV2,
+        Provider::DeepbookV3,
+        Provider::Kriya,
+        Provider::KriyaV3,
+        Provider::FlowxV2,
+        Provider::FlowxV3,
+        Provider::Turbos,
+        Provider::Aftermath,
+        Provider::Haedal,
+        Provider::HaedalPmm,
+        Provider::Volo,
+        Provider::Afsui,
+        Provider::Bluemove,
+        Provider::Scallop,
+        Provider::Suilend,
+        Provider::Bluefin,
+        Provider::Alphafi,
+        Provider::Springsui,
+        Provider::Steamm,
+        Provider::Metastable,
+        Provider::Obric,
+    ];
+
+    /// APIで使用するプロバイダー名
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Cetus => "CETUS",
+            Self::DeepbookV2 => "DEEPBOOKV2",
+            Self::DeepbookV3 => "DEEPBOOKV3",
+            Self::Kriya => "KRIYA",
+            Self::KriyaV3 => "KRIYAV3",
+            Self::FlowxV2 => "FLOWXV2",
+            Self::FlowxV3 => "FLOWXV3",
+            Self::Turbos => "TURBOS",
+            Self::Aftermath => "AFTERMATH",
+            Self::Haedal => "HAEDAL",
+            Self::HaedalPmm => "HAEDALPMM",
+            Self::Volo => "VOLO",
+            Self::Afsui => "AFSUI",
+            Self::Bluemove => "BLUEMOVE",
+            Self::Scallop => "SCALLOP",
+            Self::Suilend => "SUILEND",
+            Self::Bluefin => "BLUEFIN",
+            Self::Alphafi => "ALPHAFI",
+            Self::Springsui => "SPRINGSUI",
+            Self::Steamm => "STEAMM",
+            Self::Metastable => "METASTABLE",
+            Self::Obric => "OBRIC",
+            Self::Unknown(name) => name,
+        }
+    }
+
+    /// このクレートが認識しないプロバイダーかどうか
+    pub fn is_unknown(&self) -> bool {
+        matches!(self, Self::Unknown(_))
+    }
+}
+
+impl fmt::Display for Provider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// プロバイダー名から変換（認識しているプロバイダーとの照合では大文字・小文字を区別しない）
+///
+/// 認識できない名前は元の文字列のまま[`Provider::Unknown`]になるため、変換は失敗しません。
+impl FromStr for Provider {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim();
+        Ok(Self::ALL
+            .iter()
+            .find(|provider| provider.as_str().eq_ignore_ascii_case(name))
+            .cloned()
+            .unwrap_or_else(|| Self::Unknown(s.to_string())))
+    }
+}
+
+impl Serialize for Provider {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for Provider {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        Ok(s.parse().unwrap_or_else(|never| match never {}))
+    }
+}
+
+/// ルート検索で使用するプロバイダーの指定
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum ProviderFilter {
+    /// サーバーのデフォルト（すべてのプロバイダー）を使用
+    #[default]
+    All,
+    /// 指定したプロバイダーのみを使用
+    Only(Vec<Provider>),
+    /// 指定したプロバイダー以外のすべてを使用
+    ///
+    /// リクエスト時に[`Provider::ALL`]から除外したリストに展開されます。
+    Except(Vec<Provider>),
+}
+
+impl ProviderFilter {
+    /// サーバーのデフォルトを使用するかどうか
+    pub fn is_all(&self) -> bool {
+        matches!(self, Self::All)
+    }
+
+    /// リクエストで送信するプロバイダーの一覧に展開
+    ///
+    /// サーバーのデフォルトを使用する場合は`None`を返します。
+    pub fn resolve(&self) -> Option<Vec<Provider>> {
+        match self {
+            Self::All => None,
+            Self::Only(providers) => Some(providers.clone()),
+            Self::Except(excluded) => Some(
+                Provider::ALL
+                    .iter()
+                    .filter(|provider| !excluded.contains(provider))
+                    .cloned()
+                    .collect(),
+            ),
+        }
+    }
+
+    /// リクエストで送信するカンマ区切りのプロバイダー名
+    ///
+    /// サーバーのデフォルトを使用する場合、または展開結果が空の場合は`None`を返します。
+    pub fn to_query_value(&self) -> Option<String> {
+        let providers = self.resolve()?;
+        if providers.is_empty() {
+            return None;
+        }
+        Some(
+            providers
+                .iter()
+                .map(Provider::as_str)
+                .collect::<Vec<_>>()
+                .join(","),
+        )
+    }
+}
+
+impl From<Vec<Provider>> for ProviderFilter {
+    fn from(providers: Vec<Provider>) -> Self {
+        Self::Only(providers)
+    }
+}
+
+/// 展開したプロバイダー名の配列としてシリアライズ
+impl Serialize for ProviderFilter {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self.resolve() {
+            Some(providers) => providers.serialize(serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+}
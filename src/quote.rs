@@ -0,0 +1,203 @@
+/**
+ * 見積もりの計算ヘルパー
+ *
+ * このモジュールはルート検索結果からスリッページを考慮した最小出力量・最大入力量、
+ * 約定価格、価格インパクトを整数・有理数演算で正確に計算するヘルパーを定義します。
+ */
+use primitive_types::U256;
+
+use crate::amount::{amount_overflow, mul_div, mul_div_ceil};
+use crate::error::{AggregatorError, Result};
+use crate::locale::tr;
+use crate::models::RouterData;
+use crate::ratio::Ratio;
+
+/// ベーシスポイントの分母（1bps = 0.01%）
+pub const BPS_DENOMINATOR: u32 = 10_000;
+
+/// 価格インパクト
+///
+/// 価格は最小単位あたりの出力量（出力量/入力量）として扱います。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PriceImpact {
+    /// 対象のルートの位置（全体の場合は`None`）
+    pub route: Option<usize>,
+    /// 取引前の価格
+    pub initial_price: Ratio,
+    /// 約定価格
+    pub execution_price: Ratio,
+    /// 取引前の価格に対する約定価格の乖離率（0〜1）
+    pub impact: Ratio,
+    /// 約定価格が取引前の価格より有利な場合は真
+    pub favorable: bool,
+}
+
+impl PriceImpact {
+    /// 取引前の価格と約定価格から価格インパクトを計算
+    fn new(route: Option<usize>, initial_price: Ratio, execution_price: Ratio) -> Option<Self> {
+        let favorable = execution_price > initial_price;
+        let difference = if favorable {
+            execution_price.checked_sub(&initial_price)?
+        } else {
+            initial_price.checked_sub(&execution_price)?
+        };
+
+        Some(Self {
+            route,
+            initial_price,
+            execution_price,
+            impact: difference.checked_div(&initial_price)?,
+            favorable,
+        })
+    }
+
+    /// 乖離率をベーシスポイントで取得（切り上げ）
+    pub fn bps(&self) -> U256 {
+        self.impact
+            .mul_ceil(U256::from(BPS_DENOMINATOR))
+            .unwrap_or(U256::MAX)
+    }
+}
+
+impl RouterData {
+    /// スリッページを考慮した最小出力量を計算（入力量ベースの見積もり用）
+    ///
+    /// `amount_out × (10000 - slippage_bps) / 10000`を切り捨てで計算します。
+    ///
+    /// # 引数
+    ///
+    /// * `slippage_bps` - 許容するスリッページ（ベーシスポイント、10000以下）
+    ///
+    /// # 戻り値
+    ///
+    /// 最小出力量。出力量ベースの見積もり、またはスリッページが範囲外の場合は`InputError`、
+    /// 入力量ベースフラグがない場合や計算がオーバーフローした場合は`InvalidResponse`を返します。
+    pub fn min_amount_out(&self, slippage_bps: u32) -> Result<U256> {
+        if !self.quote_by_amount_in()? {
+            return Err(AggregatorError::InputError(tr!(
+                "出力量ベースの見積もりにはmax_amount_inを使用してください",
+                "use max_amount_in for quotes by amount out"
+            )));
+        }
+        let slippage = validate_slippage(slippage_bps)?;
+
+        mul_div(
+            self.amount_out,
+            U256::from(BPS_DENOMINATOR - slippage),
+            U256::from(BPS_DENOMINATOR),
+        )
+        .ok_or_else(amount_overflow)
+    }
+
+    /// スリッページを考慮した最大入力量を計算（出力量ベースの見積もり用）
+    ///
+    /// `amount_in × (10000 + slippage_bps) / 10000`を切り上げで計算します。
+    ///
+    /// # 引数
+    ///
+    /// * `slippage_bps` - 許容するスリッページ（ベーシスポイント、10000以下）
+    ///
+    /// # 戻り値
+    ///
+    /// 最大入力量。入力量ベースの見積もり、またはスリッページが範囲外の場合は`InputError`、
+    /// 入力量ベースフラグがない場合や計算がオーバーフローした場合は`InvalidResponse`を返します。
+    pub fn max_amount_in(&self, slippage_bps: u32) -> Result<U256> {
+        if self.quote_by_amount_in()? {
+            return Err(AggregatorError::InputError(tr!(
+                "入力量ベースの見積もりにはmin_amount_outを使用してください",
+                "use min_amount_out for quotes by amount in"
+            )));
+        }
+        let slippage = validate_slippage(slippage_bps)?;
+
+        mul_div_ceil(
+            self.amount_in,
+            U256::from(BPS_DENOMINATOR + slippage),
+            U256::from(BPS_DENOMINATOR),
+        )
+        .ok_or_else(amount_overflow)
+    }
+
+    /// 見積もりが入力量ベースかどうか
+    ///
+    /// クライアントはレスポンスにないフラグをリクエストの値で補完するため、通常は常に存在します。
+    /// クライアントを経由せずに構築したデータでフラグがない場合は、どちらの金額が固定されているかを
+    /// 判断できないため、エラーを返します。
+    fn quote_by_amount_in(&self) -> Result<bool> {
+        self.by_amount_in.ok_or_else(|| {
+            AggregatorError::InvalidResponse(tr!(
+                "レスポンスに入力量ベースフラグが含まれていないため、見積もりの方向を判断できません",
+                "response does not contain by_amount_in, so the quote direction is unknown"
+            ))
+        })
+    }
+
+    /// 約定価格（出力量/入力量）
+    ///
+    /// 入力量が0の場合は`None`を返します。
+    pub fn execution_price(&self) -> Option<Ratio> {
+        Ratio::new(self.amount_out, self.amount_in)
+    }
+
+    /// 各ルートの`initial_price`に対する価格インパクト
+    ///
+    /// # 戻り値
+    ///
+    /// ルートごとの価格インパクト。入力量または取引前の価格が0のルートは含まれません。
+    pub fn route_price_impacts(&self) -> Vec<PriceImpact> {
+        self.routes
+            .iter()
+            .enumerate()
+            .filter_map(|(i, route)| {
+                let execution_price = Ratio::new(route.amount_out, route.amount_in)?;
+                PriceImpact::new(Some(i), route.initial_price, execution_price)
+            })
+            .collect()
+    }
+
+    /// 見積もり全体の価格インパクト
+    ///
+    /// 各ルートの入力量を`initial_price`で換算した出力量の合計を取引前の価格の基準とし、
+    /// 実際の総出力量と比較します。
+    ///
+    /// # 戻り値
+    ///
+    /// 価格インパクト。ルートがない、または入力量が0の場合は`None`を返します。
+    /// 計算がオーバーフローした場合はエラーを返します。
+    pub fn price_impact(&self) -> Result<Option<PriceImpact>> {
+        let mut ideal_out = Ratio::zero();
+        for route in &self.routes {
+            ideal_out = route
+                .initial_price
+                .checked_mul(&Ratio::from_integer(route.amount_in))
+                .and_then(|out| ideal_out.checked_add(&out))
+                .ok_or_else(amount_overflow)?;
+        }
+
+        let Some(execution_price) = self.execution_price() else {
+            return Ok(None);
+        };
+        let Some(initial_price) = ideal_out.checked_div(&Ratio::from_integer(self.amount_in))
+        else {
+            return Ok(None);
+        };
+        if initial_price.is_zero() {
+            return Ok(None);
+        }
+
+        Ok(PriceImpact::new(None, initial_price, execution_price))
+    }
+}
+
+/// スリッページが範囲内かを検証
+fn validate_slippage(slippage_bps: u32) -> Result<u32> {
+    if slippage_bps > BPS_DENOMINATOR {
+        return Err(AggregatorError::InputError(tr!(
+            "スリッページは{}bps以下で指定してください: {}",
+            "slippage must be at most {}bps: {}",
+            BPS_DENOMINATOR,
+            slippage_bps
+        )));
+    }
+    Ok(slippage_bps)
+}
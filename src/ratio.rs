@@ -0,0 +1,346 @@
+/**
+ * 正確な有理数
+ *
+ * このモジュールは価格や手数料率を誤差なく扱うための非負の有理数型を定義します。
+ * 10進数の文字列（指数表記を含む）から正確に変換でき、`f64`による丸め誤差が発生しません。
+ */
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+use primitive_types::{U256, U512};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+
+use crate::amount::{mul_div, mul_div_ceil};
+use crate::error::AggregatorError;
+use crate::locale::tr;
+
+/// `Display`で出力する小数点以下の最大桁数
+const DISPLAY_SCALE: u32 = 18;
+
+/// 解析で保持する小数点以下の最大桁数（分母の`10^77`が`U256`に収まる上限）
+const MAX_PARSE_SCALE: usize = 77;
+
+/// 非負の有理数（分子/分母）
+///
+/// 常に既約分数として保持し、分母は0になりません。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Ratio {
+    numer: U256,
+    denom: U256,
+}
+
+impl Ratio {
+    /// 分子と分母から有理数を作成
+    ///
+    /// 分母が0の場合は`None`を返します。
+    pub fn new(numer: U256, denom: U256) -> Option<Self> {
+        if denom.is_zero() {
+            return None;
+        }
+        let g = gcd(numer, denom);
+        Some(Self {
+            numer: numer / g,
+            denom: denom / g,
+        })
+    }
+
+    /// 整数から有理数を作成
+    pub fn from_integer(value: U256) -> Self {
+        Self {
+            numer: value,
+            denom: U256::one(),
+        }
+    }
+
+    /// 0
+    pub fn zero() -> Self {
+        Self::from_integer(U256::zero())
+    }
+
+    /// 1
+    pub fn one() -> Self {
+        Self::from_integer(U256::one())
+    }
+
+    /// 分子
+    pub fn numer(&self) -> U256 {
+        self.numer
+    }
+
+    /// 分母
+    pub fn denom(&self) -> U256 {
+        self.denom
+    }
+
+    /// 0かどうか
+    pub fn is_zero(&self) -> bool {
+        self.numer.is_zero()
+    }
+
+    /// `U512`で計算した分子と分母から既約分数を作成
+    ///
+    /// 約分後も`U256`に収まらない場合は`None`を返します。
+    fn from_wide(numer: U512, denom: U512) -> Option<Self> {
+        if denom.is_zero() {
+            return None;
+        }
+        let g = gcd(numer, denom);
+        Some(Self {
+            numer: U256::try_from(numer / g).ok()?,
+            denom: U256::try_from(denom / g).ok()?,
+        })
+    }
+
+    /// 加算（オーバーフローした場合は`None`）
+    pub fn checked_add(&self, other: &Self) -> Option<Self> {
+        let numer = U512::from(self.numer) * U512::from(other.denom)
+            + U512::from(other.numer) * U512::from(self.denom);
+        let denom = U512::from(self.denom) * U512::from(other.denom);
+        Self::from_wide(numer, denom)
+    }
+
+    /// 減算（結果が負になる場合、またはオーバーフローした場合は`None`）
+    pub fn checked_sub(&self, other: &Self) -> Option<Self> {
+        let lhs = U512::from(self.numer) * U512::from(other.denom);
+        let rhs = U512::from(other.numer) * U512::from(self.denom);
+        if lhs < rhs {
+            return None;
+        }
+        Self::from_wide(lhs - rhs, U512::from(self.denom) * U512::from(other.denom))
+    }
+
+    /// 乗算（オーバーフローした場合は`None`）
+    pub fn checked_mul(&self, other: &Self) -> Option<Self> {
+        Self::from_wide(
+            U512::from(self.numer) * U512::from(other.numer),
+            U512::from(self.denom) * U512::from(other.denom),
+        )
+    }
+
+    /// 除算（`other`が0の場合、またはオーバーフローした場合は`None`）
+    pub fn checked_div(&self, other: &Self) -> Option<Self> {
+        Self::from_wide(
+            U512::from(self.numer) * U512::from(other.denom),
+            U512::from(self.denom) * U512::from(other.numer),
+        )
+    }
+
+    /// 金額に掛けて切り捨てた値（結果が`U256`に収まらない場合は`None`）
+    pub fn mul_floor(&self, amount: U256) -> Option<U256> {
+        mul_div(amount, self.numer, self.denom)
+    }
+
+    /// 金額に掛けて切り上げた値（結果が`U256`に収まらない場合は`None`）
+    pub fn mul_ceil(&self, amount: U256) -> Option<U256> {
+        mul_div_ceil(amount, self.numer, self.denom)
+    }
+
+    /// 小数点以下`scale`桁で切り捨てた10進数の文字列
+    pub fn to_decimal_string(&self, scale: u32) -> String {
+        let integer = self.numer / self.denom;
+        if scale == 0 {
+            return integer.to_string();
+        }
+
+        // 小数部分は10倍ずつ桁を求める（剰余は分母未満のためU512でオーバーフローしない）
+        let denom = U512::from(self.denom);
+        let mut remainder = U512::from(self.numer % self.denom);
+        let mut fraction = String::with_capacity(scale as usize);
+        for _ in 0..scale {
+            remainder *= U512::from(10u8);
+            fraction.push(char::from(b'0' + (remainder / denom).low_u32() as u8));
+            remainder %= denom;
+        }
+        format!("{}.{}", integer, fraction)
+    }
+
+    /// 有限小数で表せる場合は正確な10進数の文字列、表せない場合は`None`
+    pub fn to_exact_decimal_string(&self) -> Option<String> {
+        // 既約分数の分母が2と5の累乗の積である場合のみ有限小数になる
+        let mut denom = self.denom;
+        let (mut twos, mut fives) = (0u32, 0u32);
+        while (denom % U256::from(2u8)).is_zero() {
+            denom /= U256::from(2u8);
+            twos += 1;
+        }
+        while (denom % U256::from(5u8)).is_zero() {
+            denom /= U256::from(5u8);
+            fives += 1;
+        }
+        if denom != U256::one() {
+            return None;
+        }
+
+        let s = self.to_decimal_string(twos.max(fives));
+        Some(if s.contains('.') {
+            s.trim_end_matches('0').trim_end_matches('.').to_string()
+        } else {
+            s
+        })
+    }
+
+    /// `f64`に変換した近似値（表示やログ用）
+    pub fn to_f64(&self) -> f64 {
+        self.to_decimal_string(DISPLAY_SCALE)
+            .parse()
+            .unwrap_or(f64::NAN)
+    }
+}
+
+impl PartialOrd for Ratio {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Ratio {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let lhs = U512::from(self.numer) * U512::from(other.denom);
+        let rhs = U512::from(other.numer) * U512::from(self.denom);
+        lhs.cmp(&rhs)
+    }
+}
+
+/// 小数点以下18桁までの10進数で出力（末尾の0は省略、それ以降は切り捨て）
+impl fmt::Display for Ratio {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = self.to_decimal_string(DISPLAY_SCALE);
+        f.write_str(s.trim_end_matches('0').trim_end_matches('.'))
+    }
+}
+
+/// 10進数の文字列（`123`、`0.0025`、`1.5e-7`など）または分数（`1/3`）から正確に変換
+///
+/// 分母が`U256`に収まらない小数点以下77桁より細かい部分は四捨五入するため、
+/// 非常に小さい値は0になります。
+impl FromStr for Ratio {
+    type Err = AggregatorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let parsed = match s.split_once('/') {
+            Some((numer, denom)) => parse_decimal(numer.trim())
+                .zip(parse_decimal(denom.trim()))
+                .and_then(|(numer, denom)| numer.checked_div(&denom)),
+            None => parse_decimal(s),
+        };
+        parsed.ok_or_else(|| {
+            AggregatorError::InputError(tr!("不正な数値です: {}", "invalid number: {}", s))
+        })
+    }
+}
+
+/// 有限小数で表せる場合は10進数、表せない場合は分数の文字列としてシリアライズ
+impl Serialize for Ratio {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self.to_exact_decimal_string() {
+            Some(s) => serializer.serialize_str(&s),
+            None => serializer.serialize_str(&format!("{}/{}", self.numer, self.denom)),
+        }
+    }
+}
+
+/// JSONの数値と文字列のどちらからでも精度を失わずにデシリアライズ
+impl<'de> Deserialize<'de> for Ratio {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = Value::deserialize(deserializer)?;
+        let parsed = match &value {
+            Value::String(s) => s.parse().ok(),
+            Value::Number(n) => n.to_string().parse().ok(),
+            _ => None,
+        };
+        parsed.ok_or_else(|| {
+            serde::de::Error::custom(tr!("不正な数値です: {}", "invalid number: {}", value))
+        })
+    }
+}
+
+/// 10進数の文字列を有理数に変換
+fn parse_decimal(s: &str) -> Option<Ratio> {
+    let s = s.strip_prefix('+').unwrap_or(s);
+    let (mantissa, exponent) = match s.find(['e', 'E']) {
+        Some(i) => (&s[..i], s[i + 1..].parse::<i32>().ok()?),
+        None => (s, 0),
+    };
+
+    let (integer, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    if integer.is_empty() && fraction.is_empty() {
+        return None;
+    }
+    if !integer
+        .bytes()
+        .chain(fraction.bytes())
+        .all(|b| b.is_ascii_digit())
+    {
+        return None;
+    }
+
+    // 小数部の末尾の0は値に影響しないため、桁数に数えない
+    let fraction = fraction.trim_end_matches('0');
+    let digits = format!("{}{}", integer, fraction);
+    let digits = digits.trim_start_matches('0');
+    if digits.is_empty() {
+        return Some(Ratio::zero());
+    }
+
+    // 値 = digits × 10^(exponent - 小数部の桁数)
+    let mut scale = exponent.checked_sub(i32::try_from(fraction.len()).ok()?)?;
+    let mut digits = digits;
+    let mut round_up = false;
+    if scale < 0 {
+        // 分母と分子が`U256`に収まるよう、小数点以下の細かい桁を四捨五入する
+        let fraction_digits = scale.unsigned_abs() as usize;
+        let excess = fraction_digits
+            .saturating_sub(MAX_PARSE_SCALE)
+            .max(digits.len().saturating_sub(MAX_PARSE_SCALE))
+            .min(fraction_digits);
+        if excess > 0 {
+            let keep = digits.len().saturating_sub(excess);
+            round_up = excess <= digits.len() && digits.as_bytes()[keep] >= b'5';
+            digits = &digits[..keep];
+            scale += i32::try_from(excess).ok()?;
+        }
+    }
+    let numer = if digits.is_empty() {
+        U256::zero()
+    } else {
+        U256::from_dec_str(digits).ok()?
+    };
+    let numer = if round_up {
+        numer.checked_add(U256::one())?
+    } else {
+        numer
+    };
+    let power = pow10(scale.unsigned_abs())?;
+    if scale >= 0 {
+        Some(Ratio::from_integer(numer.checked_mul(power)?))
+    } else {
+        Ratio::new(numer, power)
+    }
+}
+
+/// 10のべき乗（`U256`に収まらない場合は`None`）
+fn pow10(exponent: u32) -> Option<U256> {
+    U256::from(10u8).checked_pow(U256::from(exponent))
+}
+
+/// 最大公約数（ユークリッドの互除法）
+fn gcd<T>(mut a: T, mut b: T) -> T
+where
+    T: Copy + PartialEq + std::ops::Rem<Output = T> + Default,
+{
+    while b != T::default() {
+        let r = a % b;
+        a = b;
+        b = r;
+    }
+    a
+}
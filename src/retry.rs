@@ -0,0 +1,105 @@
+/**
+ * リトライポリシーの定義
+ *
+ * このモジュールはリクエスト失敗時の再試行間隔（指数バックオフとジッター）を定義します。
+ */
+use std::time::Duration;
+
+use rand::Rng;
+
+use crate::error::AggregatorError;
+
+/// リトライポリシー
+///
+/// 再試行可能なエラー（[`AggregatorError::is_retryable`]）が発生した場合に、
+/// 指数バックオフで待機してからリクエストを再送します。
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    /// 最大リトライ回数（0の場合はリトライしない）
+    pub max_retries: u32,
+    /// 初回リトライまでの待機時間
+    pub initial_backoff: Duration,
+    /// 待機時間の上限
+    pub max_backoff: Duration,
+    /// リトライごとの待機時間の倍率
+    pub multiplier: f64,
+    /// ジッターの割合（0.0〜1.0、待機時間をこの割合の範囲でランダムに短縮）
+    pub jitter: f64,
+    /// `Retry-After`ヘッダーの値を優先するかどうか
+    ///
+    /// 指定された待機時間が`max_backoff`を超える場合はリトライせずにエラーを返します。
+    pub respect_retry_after: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(5),
+            multiplier: 2.0,
+            jitter: 0.5,
+            respect_retry_after: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// リトライを行わないポリシー
+    pub fn none() -> Self {
+        Self {
+            max_retries: 0,
+            ..Self::default()
+        }
+    }
+
+    /// ジッターを適用する前のバックオフ時間を計算
+    ///
+    /// # 引数
+    ///
+    /// * `attempt` - 何回目のリトライか（0始まり）
+    pub fn base_backoff(&self, attempt: u32) -> Duration {
+        let factor = self
+            .multiplier
+            .max(1.0)
+            .powi(attempt.min(i32::MAX as u32) as i32);
+        let backoff = self.initial_backoff.as_secs_f64() * factor;
+        let backoff = backoff.min(self.max_backoff.as_secs_f64());
+        Duration::try_from_secs_f64(backoff).unwrap_or(self.max_backoff)
+    }
+
+    /// ジッターを適用したバックオフ時間を計算
+    ///
+    /// `jitter`がNaNの場合はジッターを適用しません。
+    ///
+    /// # 引数
+    ///
+    /// * `attempt` - 何回目のリトライか（0始まり）
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let base = self.base_backoff(attempt);
+        let jitter = if self.jitter.is_nan() {
+            0.0
+        } else {
+            self.jitter.clamp(0.0, 1.0)
+        };
+        if jitter == 0.0 {
+            return base;
+        }
+
+        let scale = 1.0 - jitter * rand::thread_rng().gen::<f64>();
+        base.mul_f64(scale)
+    }
+
+    /// エラーに応じた次回リトライまでの待機時間を計算
+    ///
+    /// `respect_retry_after`が有効で、エラーに`Retry-After`の指定がある場合はその値を使用します。
+    /// 指定された待機時間が`max_backoff`を超える場合はリトライしないため`None`を返します。
+    pub(crate) fn delay_for(&self, attempt: u32, error: &AggregatorError) -> Option<Duration> {
+        match error.retry_after() {
+            Some(retry_after) if self.respect_retry_after => {
+                (retry_after <= self.max_backoff).then_some(retry_after)
+            }
+            _ => Some(self.backoff(attempt)),
+        }
+    }
+}
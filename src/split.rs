@@ -0,0 +1,133 @@
+/**
+ * 取引の分割方法
+ *
+ * このモジュールは取引を複数のルートに分割する際のアルゴリズムと、そのパラメータを定義します。
+ * APIの公開仕様はアルゴリズム名を定義していないため、名前はサーバーのデフォルトを使用するか、
+ * 呼び出し側が指定します。
+ */
+use serde::ser::SerializeMap;
+use serde::{Serialize, Serializer};
+
+use crate::locale::{tr, Locale};
+
+/// 分割数の最小値
+///
+/// 最大値はAPIの公開仕様で定義されていないため、必要な場合は[`ParamLimits`](crate::models::ParamLimits)で設定します。
+pub const MIN_SPLIT_COUNT: u32 = 1;
+
+/// 取引の分割アルゴリズム
+///
+/// APIには`split_algorithm`・`split_factor`・`split_count`の各パラメータとして送信されます。
+#[derive(Debug, Clone, PartialEq)]
+pub enum SplitAlgorithm {
+    /// サーバーのデフォルトのアルゴリズムで最大`count`ルートに分割
+    ///
+    /// `split_algorithm`は送信せず、`split_count`のみを送信します。
+    Auto {
+        /// 最大分割数
+        count: u32,
+    },
+    /// 名前を指定したアルゴリズム
+    ///
+    /// 名前と係数はそのまま`split_algorithm`・`split_factor`として送信します。
+    Custom {
+        /// アルゴリズム名
+        name: String,
+        /// 分割係数（オプション）
+        factor: Option<f64>,
+        /// 最大分割数（オプション）
+        count: Option<u32>,
+    },
+}
+
+impl SplitAlgorithm {
+    /// APIで使用するアルゴリズム名（サーバーのデフォルトを使用する場合は`None`）
+    pub fn name(&self) -> Option<&str> {
+        match self {
+            Self::Auto { .. } => None,
+            Self::Custom { name, .. } => Some(name),
+        }
+    }
+
+    /// 分割係数
+    pub fn factor(&self) -> Option<f64> {
+        match self {
+            Self::Auto { .. } => None,
+            Self::Custom { factor, .. } => *factor,
+        }
+    }
+
+    /// 最大分割数
+    pub fn count(&self) -> Option<u32> {
+        match self {
+            Self::Auto { count } => Some(*count),
+            Self::Custom { count, .. } => *count,
+        }
+    }
+
+    /// パラメータが不正である理由の一覧
+    pub(crate) fn violations(&self, max_count: Option<u32>, locale: Locale) -> Vec<String> {
+        let mut errors = Vec::new();
+
+        if let Some(count) = self.count() {
+            match max_count {
+                Some(max) if !(MIN_SPLIT_COUNT..=max).contains(&count) => errors.push(tr!(
+                    locale =>
+                    "split_countは{}以上{}以下で指定してください: {}",
+                    "split_count must be between {} and {}: {}",
+                    MIN_SPLIT_COUNT,
+                    max,
+                    count
+                )),
+                None if count < MIN_SPLIT_COUNT => errors.push(tr!(
+                    locale =>
+                    "split_countは{}以上で指定してください: {}",
+                    "split_count must be at least {}: {}",
+                    MIN_SPLIT_COUNT,
+                    count
+                )),
+                _ => {}
+            }
+        }
+
+        if let Self::Custom { name, factor, .. } = self {
+            if name.trim().is_empty() {
+                errors.push(tr!(
+                    locale =>
+                    "split_algorithmの名前が空です",
+                    "split_algorithm name is empty"
+                ));
+            }
+            if let Some(factor) = factor.filter(|factor| !factor.is_finite()) {
+                errors.push(tr!(
+                    locale =>
+                    "split_factorには有限の値を指定してください: {}",
+                    "split_factor must be finite: {}",
+                    factor
+                ));
+            }
+        }
+
+        errors
+    }
+}
+
+/// `split_algorithm`・`split_factor`・`split_count`のうち、値のあるものだけを含むマップとしてシリアライズ
+impl Serialize for SplitAlgorithm {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(None)?;
+        if let Some(name) = self.name() {
+            map.serialize_entry("split_algorithm", name)?;
+        }
+        if let Some(factor) = self.factor() {
+            map.serialize_entry("split_factor", &factor)?;
+        }
+        if let Some(count) = self.count() {
+            map.serialize_entry("split_count", &count)?;
+        }
+        map.end()
+    }
+}
@@ -0,0 +1,472 @@
+/**
+ * ルート検索結果の整合性検証
+ *
+ * このモジュールはAPIから返されたルートが、リクエストした内容と整合しているかを検証します。
+ * 署名前に不正または壊れたレスポンスを検出するために使用します。
+ */
+use std::fmt;
+
+use primitive_types::U256;
+use thiserror::Error;
+
+use crate::amount::checked_sum;
+use crate::coin::CoinType;
+use crate::details::ExtendedDetailsError;
+use crate::locale::{global_locale, tr, Locale};
+use crate::models::{FindRouterParams, RouterData};
+
+/// ルート検証で検出された違反
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum RouteViolation {
+    /// ルートが1つも含まれていない
+    NoRoutes,
+
+    /// パスが空のルート
+    EmptyPath {
+        /// ルートの位置
+        route: usize,
+    },
+
+    /// 前のホップの交換先と次のホップの交換元が一致しない
+    DiscontinuousPath {
+        /// ルートの位置
+        route: usize,
+        /// ホップの位置
+        hop: usize,
+        /// 前のホップの交換先
+        expected: CoinType,
+        /// このホップの交換元
+        actual: CoinType,
+    },
+
+    /// 前のホップの出力量と次のホップの入力量が一致しない
+    HopAmountMismatch {
+        /// ルートの位置
+        route: usize,
+        /// ホップの位置
+        hop: usize,
+        /// 前のホップの出力量
+        expected: U256,
+        /// このホップの入力量
+        actual: U256,
+    },
+
+    /// ホップの拡張詳細情報に必須フィールドがない、または型が不正
+    InvalidExtendedDetails {
+        /// ルートの位置
+        route: usize,
+        /// ホップの位置
+        hop: usize,
+        /// 変換エラー
+        error: ExtendedDetailsError,
+    },
+
+    /// 最初のホップの交換元がリクエストと一致しない
+    FromMismatch {
+        /// ルートの位置
+        route: usize,
+        /// リクエストした交換元
+        expected: CoinType,
+        /// 最初のホップの交換元
+        actual: CoinType,
+    },
+
+    /// 最後のホップの交換先がリクエストと一致しない
+    TargetMismatch {
+        /// ルートの位置
+        route: usize,
+        /// リクエストした交換先
+        expected: CoinType,
+        /// 最後のホップの交換先
+        actual: CoinType,
+    },
+
+    /// ルートの入力量が最初のホップの入力量と一致しない
+    RouteAmountInMismatch {
+        /// ルートの位置
+        route: usize,
+        /// ルートの入力量
+        expected: U256,
+        /// 最初のホップの入力量
+        actual: U256,
+    },
+
+    /// ルートの出力量が最後のホップの出力量と一致しない
+    RouteAmountOutMismatch {
+        /// ルートの位置
+        route: usize,
+        /// ルートの出力量
+        expected: U256,
+        /// 最後のホップの出力量
+        actual: U256,
+    },
+
+    /// 各ルートの入力量の合計が総入力量と一致しない
+    TotalAmountInMismatch {
+        /// 総入力量
+        expected: U256,
+        /// 各ルートの入力量の合計
+        actual: U256,
+    },
+
+    /// 各ルートの出力量の合計が総出力量と一致しない
+    TotalAmountOutMismatch {
+        /// 総出力量
+        expected: U256,
+        /// 各ルートの出力量の合計
+        actual: U256,
+    },
+
+    /// 金額の合計がオーバーフローした
+    AmountOverflow,
+
+    /// 固定側の金額がリクエストした金額と一致しない
+    RequestedAmountMismatch {
+        /// リクエストした金額
+        expected: U256,
+        /// レスポンスの固定側の金額
+        actual: U256,
+    },
+
+    /// 入力量ベースフラグがリクエストと一致しない
+    DirectionMismatch {
+        /// リクエストした入力量ベースフラグ
+        expected: bool,
+        /// レスポンスの入力量ベースフラグ
+        actual: bool,
+    },
+
+    /// レスポンスに入力量ベースフラグが含まれていない
+    MissingDirection {
+        /// リクエストした入力量ベースフラグ
+        expected: bool,
+    },
+
+    /// ホップ数がリクエストした深さを超えている
+    DepthExceeded {
+        /// ルートの位置
+        route: usize,
+        /// ホップ数
+        hops: usize,
+        /// リクエストした深さ
+        depth: u32,
+    },
+}
+
+impl RouteViolation {
+    /// 指定したロケールで表示用の文字列に変換
+    pub fn to_localized_string(&self, locale: Locale) -> String {
+        match self {
+            Self::NoRoutes => tr!(
+                locale =>
+                "ルートが含まれていません",
+                "response contains no routes"
+            ),
+            Self::EmptyPath { route } => tr!(
+                locale =>
+                "ルート{}のパスが空です",
+                "route {} has an empty path",
+                route
+            ),
+            Self::DiscontinuousPath {
+                route,
+                hop,
+                expected,
+                actual,
+            } => tr!(
+                locale =>
+                "ルート{}のホップ{}が連続していません（期待値: {}, 実際: {}）",
+                "hop {1} of route {0} is not continuous (expected: {2}, actual: {3})",
+                route,
+                hop,
+                expected,
+                actual
+            ),
+            Self::HopAmountMismatch {
+                route,
+                hop,
+                expected,
+                actual,
+            } => tr!(
+                locale =>
+                "ルート{}のホップ{}の入力量が前のホップの出力量と一致しません（期待値: {}, 実際: {}）",
+                "amount in of hop {1} of route {0} does not match the previous hop's amount out (expected: {2}, actual: {3})",
+                route,
+                hop,
+                expected,
+                actual
+            ),
+            Self::InvalidExtendedDetails { route, hop, error } => tr!(
+                locale =>
+                "ルート{}のホップ{}の拡張詳細情報が不正です: {}",
+                "extended details of hop {1} of route {0} are invalid: {2}",
+                route,
+                hop,
+                error.to_localized_string(locale)
+            ),
+            Self::FromMismatch {
+                route,
+                expected,
+                actual,
+            } => tr!(
+                locale =>
+                "ルート{}の交換元が一致しません（期待値: {}, 実際: {}）",
+                "source coin of route {} does not match (expected: {}, actual: {})",
+                route,
+                expected,
+                actual
+            ),
+            Self::TargetMismatch {
+                route,
+                expected,
+                actual,
+            } => tr!(
+                locale =>
+                "ルート{}の交換先が一致しません（期待値: {}, 実際: {}）",
+                "target coin of route {} does not match (expected: {}, actual: {})",
+                route,
+                expected,
+                actual
+            ),
+            Self::RouteAmountInMismatch {
+                route,
+                expected,
+                actual,
+            } => tr!(
+                locale =>
+                "ルート{}の入力量が最初のホップと一致しません（ルート: {}, ホップ: {}）",
+                "amount in of route {} does not match its first hop (route: {}, hop: {})",
+                route,
+                expected,
+                actual
+            ),
+            Self::RouteAmountOutMismatch {
+                route,
+                expected,
+                actual,
+            } => tr!(
+                locale =>
+                "ルート{}の出力量が最後のホップと一致しません（ルート: {}, ホップ: {}）",
+                "amount out of route {} does not match its last hop (route: {}, hop: {})",
+                route,
+                expected,
+                actual
+            ),
+            Self::TotalAmountInMismatch { expected, actual } => tr!(
+                locale =>
+                "ルートの入力量の合計が総入力量と一致しません（総入力量: {}, 合計: {}）",
+                "sum of route amounts in does not match the total (total: {}, sum: {})",
+                expected,
+                actual
+            ),
+            Self::TotalAmountOutMismatch { expected, actual } => tr!(
+                locale =>
+                "ルートの出力量の合計が総出力量と一致しません（総出力量: {}, 合計: {}）",
+                "sum of route amounts out does not match the total (total: {}, sum: {})",
+                expected,
+                actual
+            ),
+            Self::AmountOverflow => tr!(
+                locale =>
+                "ルートの金額の合計がオーバーフローしました",
+                "sum of route amounts overflowed"
+            ),
+            Self::RequestedAmountMismatch { expected, actual } => tr!(
+                locale =>
+                "固定側の金額がリクエストと一致しません（リクエスト: {}, レスポンス: {}）",
+                "fixed amount does not match the request (request: {}, response: {})",
+                expected,
+                actual
+            ),
+            Self::DirectionMismatch { expected, actual } => tr!(
+                locale =>
+                "入力量ベースフラグがリクエストと一致しません（期待値: {}, 実際: {}）",
+                "by_amount_in does not match the request (expected: {}, actual: {})",
+                expected,
+                actual
+            ),
+            Self::MissingDirection { expected } => tr!(
+                locale =>
+                "レスポンスに入力量ベースフラグが含まれていません（リクエスト: {}）",
+                "response does not contain by_amount_in (request: {})",
+                expected
+            ),
+            Self::DepthExceeded { route, hops, depth } => tr!(
+                locale =>
+                "ルート{}のホップ数{}が深さ{}を超えています",
+                "route {} has {} hops, exceeding depth {}",
+                route,
+                hops,
+                depth
+            ),
+        }
+    }
+}
+
+/// プロセス全体のロケールで表示
+impl fmt::Display for RouteViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_localized_string(global_locale()))
+    }
+}
+
+impl RouterData {
+    /// ルート検索結果がリクエストと整合しているかを検証
+    ///
+    /// 以下の項目を検証し、検出したすべての違反を返します。
+    ///
+    /// * 各ルートのパスが連続している（各ホップの交換先と出力量が次のホップの交換元と入力量に一致する）
+    /// * 各ホップの拡張詳細情報にプロバイダーの必須フィールドが揃っている
+    /// * 最初と最後のコインがリクエストと一致する
+    /// * 各ルートの金額がホップの金額と一致し、その合計が総量と一致する
+    /// * 入力量ベースフラグがレスポンスに含まれ、固定側の金額とともにリクエストと一致する
+    /// * ホップ数がリクエストした深さ以内に収まっている
+    ///
+    /// レスポンスにはプールのコインA/Bの順序が含まれないため、ホップの方向（`Path.direction`）は検証しません。
+    ///
+    /// # 引数
+    ///
+    /// * `params` - このレスポンスを取得したときのルート検索パラメータ
+    ///
+    /// # 戻り値
+    ///
+    /// 違反がない場合は`Ok(())`、違反がある場合はその一覧を返します。
+    pub fn validate(&self, params: &FindRouterParams) -> Result<(), Vec<RouteViolation>> {
+        let mut violations = Vec::new();
+
+        if self.routes.is_empty() {
+            violations.push(RouteViolation::NoRoutes);
+        }
+
+        for (route_index, route) in self.routes.iter().enumerate() {
+            let (Some(first), Some(last)) = (route.path.first(), route.path.last()) else {
+                violations.push(RouteViolation::EmptyPath { route: route_index });
+                continue;
+            };
+
+            if first.from != params.from {
+                violations.push(RouteViolation::FromMismatch {
+                    route: route_index,
+                    expected: params.from.clone(),
+                    actual: first.from.clone(),
+                });
+            }
+
+            if last.target != params.target {
+                violations.push(RouteViolation::TargetMismatch {
+                    route: route_index,
+                    expected: params.target.clone(),
+                    actual: last.target.clone(),
+                });
+            }
+
+            for (hop, pair) in route.path.windows(2).enumerate() {
+                if pair[0].target != pair[1].from {
+                    violations.push(RouteViolation::DiscontinuousPath {
+                        route: route_index,
+                        hop: hop + 1,
+                        expected: pair[0].target.clone(),
+                        actual: pair[1].from.clone(),
+                    });
+                }
+                if pair[0].amount_out != pair[1].amount_in {
+                    violations.push(RouteViolation::HopAmountMismatch {
+                        route: route_index,
+                        hop: hop + 1,
+                        expected: pair[0].amount_out,
+                        actual: pair[1].amount_in,
+                    });
+                }
+            }
+
+            for (hop, path) in route.path.iter().enumerate() {
+                if let Some(error) = path.extended_details_error() {
+                    violations.push(RouteViolation::InvalidExtendedDetails {
+                        route: route_index,
+                        hop,
+                        error,
+                    });
+                }
+            }
+
+            if route.amount_in != first.amount_in {
+                violations.push(RouteViolation::RouteAmountInMismatch {
+                    route: route_index,
+                    expected: route.amount_in,
+                    actual: first.amount_in,
+                });
+            }
+
+            if route.amount_out != last.amount_out {
+                violations.push(RouteViolation::RouteAmountOutMismatch {
+                    route: route_index,
+                    expected: route.amount_out,
+                    actual: last.amount_out,
+                });
+            }
+
+            if let Some(depth) = params.depth {
+                if route.path.len() > depth as usize {
+                    violations.push(RouteViolation::DepthExceeded {
+                        route: route_index,
+                        hops: route.path.len(),
+                        depth,
+                    });
+                }
+            }
+        }
+
+        let total_in = checked_sum(self.routes.iter().map(|r| r.amount_in));
+        let total_out = checked_sum(self.routes.iter().map(|r| r.amount_out));
+        match (total_in, total_out) {
+            (Some(total_in), Some(total_out)) => {
+                if !self.routes.is_empty() && total_in != self.amount_in {
+                    violations.push(RouteViolation::TotalAmountInMismatch {
+                        expected: self.amount_in,
+                        actual: total_in,
+                    });
+                }
+                if !self.routes.is_empty() && total_out != self.amount_out {
+                    violations.push(RouteViolation::TotalAmountOutMismatch {
+                        expected: self.amount_out,
+                        actual: total_out,
+                    });
+                }
+            }
+            _ => violations.push(RouteViolation::AmountOverflow),
+        }
+
+        // フラグがない場合はどちらの金額が固定されているかを確認できないため、違反として扱う
+        match self.by_amount_in {
+            Some(by_amount_in) if by_amount_in != params.by_amount_in => {
+                violations.push(RouteViolation::DirectionMismatch {
+                    expected: params.by_amount_in,
+                    actual: by_amount_in,
+                });
+            }
+            Some(_) => {}
+            None => violations.push(RouteViolation::MissingDirection {
+                expected: params.by_amount_in,
+            }),
+        }
+
+        // 入力量ベースの場合は入力量、出力量ベースの場合は出力量がリクエストした金額と一致する
+        let fixed_amount = if params.by_amount_in {
+            self.amount_in
+        } else {
+            self.amount_out
+        };
+        if fixed_amount != params.amount {
+            violations.push(RouteViolation::RequestedAmountMismatch {
+                expected: params.amount,
+                actual: fixed_amount,
+            });
+        }
+
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+}
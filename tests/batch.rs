@@ -0,0 +1,106 @@
+//! 一括検索の結合テスト
+
+mod common;
+
+use std::time::Duration;
+
+use cetus_aggregator_rust::mock_server::{MockServer, ServerResponse};
+use cetus_aggregator_rust::{AggregatorClient, AggregatorError, DEFAULT_BATCH_CONCURRENCY};
+use primitive_types::U256;
+
+fn batch(amounts: &[u64]) -> Vec<cetus_aggregator_rust::FindRouterParams> {
+    amounts
+        .iter()
+        .map(|amount| common::params(*amount))
+        .collect()
+}
+
+#[tokio::test]
+async fn results_keep_input_order() {
+    let server = MockServer::start().await.unwrap();
+    // 金額が小さいほど遅く応答する
+    server.respond_with(|request| {
+        let amount: u64 = request.param("amount").unwrap().parse().unwrap();
+        common::doubling(request).delayed(Duration::from_millis(100 - amount * 30))
+    });
+
+    let items = server.client().find_routers_batch(batch(&[1, 2, 3])).await;
+
+    let amounts: Vec<U256> = items.iter().map(|item| item.params.amount).collect();
+    assert_eq!(amounts, [1u64, 2, 3].map(U256::from));
+    for item in &items {
+        let data = item.result.as_ref().unwrap().as_ref().unwrap();
+        assert_eq!(data.amount_out, item.params.amount * 2);
+    }
+    assert!(items[0].elapsed > items[2].elapsed);
+}
+
+#[tokio::test]
+async fn failed_items_do_not_affect_others() {
+    let server = MockServer::start().await.unwrap();
+    server.respond_with(|request| match request.param("amount").as_deref() {
+        Some("2") => ServerResponse::status(400, "Bad Request"),
+        _ => common::doubling(request),
+    });
+
+    let items = server.client().find_routers_batch(batch(&[1, 2, 3])).await;
+
+    assert!(items[0].result.is_ok());
+    assert!(matches!(
+        items[1].result,
+        Err(AggregatorError::HttpError { status: 400, .. })
+    ));
+    assert!(items[2].result.is_ok());
+}
+
+#[tokio::test]
+async fn concurrency_is_bounded_by_default() {
+    let server = MockServer::start().await.unwrap();
+    server.respond_with(|request| common::doubling(request).delayed(Duration::from_millis(100)));
+
+    let amounts: Vec<u64> = (1..=2 * DEFAULT_BATCH_CONCURRENCY as u64).collect();
+    let items = server.client().find_routers_batch(batch(&amounts)).await;
+
+    // 上限を超えた項目は先の項目が完了してから開始する
+    let started_immediately = items
+        .iter()
+        .filter(|item| item.started_after < Duration::from_millis(90))
+        .count();
+    assert_eq!(started_immediately, DEFAULT_BATCH_CONCURRENCY);
+    assert!(items.iter().all(|item| item.result.is_ok()));
+}
+
+#[tokio::test]
+async fn dropping_the_batch_cancels_pending_items() {
+    let server = MockServer::start().await.unwrap();
+    server.respond_with(|request| common::doubling(request).delayed(Duration::from_millis(50)));
+    let client = AggregatorClient::builder()
+        .endpoint(server.url())
+        .max_in_flight(1)
+        .build()
+        .unwrap();
+
+    let result = tokio::time::timeout(
+        Duration::from_millis(75),
+        client.find_routers_batch(batch(&[1, 2, 3, 4, 5])),
+    )
+    .await;
+    assert!(result.is_err());
+
+    // 中断されたタスクは次にランタイムが処理したときに待機列と送信中の数から外れる
+    tokio::time::timeout(Duration::from_secs(1), async {
+        loop {
+            let status = client.limiter_status().unwrap();
+            if (status.in_flight, status.queued) == (0, 0) {
+                break;
+            }
+            tokio::task::yield_now().await;
+        }
+    })
+    .await
+    .unwrap();
+
+    // 待機していた項目は送信されない
+    tokio::time::sleep(Duration::from_millis(150)).await;
+    assert_eq!(server.request_count(), 2);
+}
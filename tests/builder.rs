@@ -0,0 +1,82 @@
+//! クライアントビルダーの結合テスト
+
+mod common;
+
+use std::time::Duration;
+
+use cetus_aggregator_rust::mock_server::{MockServer, ServerResponse};
+use cetus_aggregator_rust::{AggregatorClient, AggregatorClientTrait, CircuitBreaker, Locale};
+
+#[tokio::test]
+async fn injected_client_sends_api_key_and_user_agent() {
+    let server = MockServer::start().await.unwrap();
+    server.respond(ServerResponse::success(common::swap(10, 20)));
+
+    let shared = reqwest::Client::new();
+    let client = AggregatorClient::builder()
+        .endpoint(server.url())
+        .http_client(shared)
+        .api_key("service-key")
+        .user_agent("pricing/1.0")
+        .default_header("x-team", "quant")
+        .build()
+        .unwrap();
+    client.find_routers(common::params(10)).await.unwrap();
+
+    let request = &server.requests()[0];
+    assert_eq!(request.header("x-api-key"), Some("service-key"));
+    assert_eq!(request.header("user-agent"), Some("pricing/1.0"));
+    assert_eq!(request.header("x-team"), Some("quant"));
+}
+
+#[tokio::test]
+async fn injected_client_rejects_client_level_options() {
+    let error = AggregatorClient::builder()
+        .http_client(reqwest::Client::new())
+        .timeout(Duration::from_secs(1))
+        .build()
+        .unwrap_err();
+    assert_eq!(error.id(), "input_error");
+}
+
+#[test]
+fn builder_errors_use_builder_locale() {
+    let error = AggregatorClient::builder()
+        .api_key("invalid\nkey")
+        .locale(Locale::English)
+        .build()
+        .unwrap_err();
+    assert_eq!(
+        error.localized(Locale::English).to_string(),
+        "invalid input parameter: invalid API key"
+    );
+}
+
+#[test]
+fn limiter_errors_use_builder_locale() {
+    let error = AggregatorClient::builder()
+        .max_in_flight(0)
+        .locale(Locale::English)
+        .build()
+        .unwrap_err();
+    assert_eq!(
+        error.localized(Locale::English).to_string(),
+        "invalid input parameter: max in-flight requests must be at least 1"
+    );
+}
+
+#[test]
+fn circuit_breaker_errors_use_builder_locale() {
+    let error = AggregatorClient::builder()
+        .circuit_breaker(CircuitBreaker {
+            timeout_rate_threshold: Some(1.5),
+            ..CircuitBreaker::default()
+        })
+        .locale(Locale::English)
+        .build()
+        .unwrap_err();
+    assert_eq!(
+        error.localized(Locale::English).to_string(),
+        "invalid input parameter: timeout rate threshold must be between 0 and 1"
+    );
+}
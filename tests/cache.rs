@@ -0,0 +1,197 @@
+//! 結果のキャッシュの結合テスト
+
+mod common;
+
+use std::time::Duration;
+
+use cetus_aggregator_rust::mock::{MockAggregatorClient, MockResponse};
+use cetus_aggregator_rust::{
+    AggregatorClientTrait, AggregatorError, AmountBucketing, CacheStats, QuoteCache, RouterData,
+};
+use primitive_types::U256;
+
+fn mock(latency: Duration) -> MockAggregatorClient {
+    let mock = MockAggregatorClient::new();
+    mock.fallback(MockResponse::route(common::swap(1_000, 3)))
+        .latency(latency);
+    mock
+}
+
+/// 同じパラメータのルート検索を同時に`count`回実行
+async fn concurrently(
+    cache: &QuoteCache<MockAggregatorClient>,
+    count: usize,
+) -> Vec<Result<Option<RouterData>, AggregatorError>> {
+    let tasks: Vec<_> = (0..count)
+        .map(|_| {
+            let cache = cache.clone();
+            tokio::spawn(async move { cache.find_routers(common::params(1_000)).await })
+        })
+        .collect();
+    let mut results = Vec::new();
+    for task in tasks {
+        results.push(task.await.unwrap());
+    }
+    results
+}
+
+#[tokio::test]
+async fn concurrent_identical_requests_are_coalesced() {
+    let mock = mock(Duration::from_millis(50));
+    let cache = QuoteCache::new(mock.clone(), Duration::from_secs(60));
+
+    let results = concurrently(&cache, 5).await;
+
+    assert!(results
+        .iter()
+        .all(|result| result.as_ref().unwrap().is_some()));
+    assert_eq!(mock.call_count(), 1);
+    assert_eq!(
+        cache.stats(),
+        CacheStats {
+            hits: 0,
+            misses: 1,
+            coalesced: 4,
+            entries: 1,
+        }
+    );
+}
+
+#[tokio::test]
+async fn results_expire_after_ttl() {
+    let mock = mock(Duration::ZERO);
+    let cache = QuoteCache::new(mock.clone(), Duration::from_millis(50));
+
+    cache.find_routers(common::params(1_000)).await.unwrap();
+    cache.find_routers(common::params(1_000)).await.unwrap();
+    assert_eq!(mock.call_count(), 1);
+    assert_eq!(cache.stats().hits, 1);
+
+    tokio::time::sleep(Duration::from_millis(60)).await;
+    cache.find_routers(common::params(1_000)).await.unwrap();
+    assert_eq!(mock.call_count(), 2);
+    assert_eq!(cache.stats().misses, 2);
+}
+
+#[tokio::test]
+async fn other_parameters_are_part_of_the_key() {
+    let mock = mock(Duration::ZERO);
+    let cache = QuoteCache::new(mock.clone(), Duration::from_secs(60));
+
+    let mut deep = common::params(1_000);
+    deep.depth = Some(3);
+    cache.find_routers(common::params(1_000)).await.unwrap();
+    cache.find_routers(deep.clone()).await.unwrap();
+    assert_eq!(mock.call_count(), 2);
+
+    cache.invalidate(&deep);
+    cache.find_routers(deep).await.unwrap();
+    assert_eq!(mock.call_count(), 3);
+}
+
+#[tokio::test]
+async fn bucketed_amounts_share_an_entry() {
+    let mock = mock(Duration::ZERO);
+    let cache = QuoteCache::new(mock.clone(), Duration::from_secs(60))
+        .with_amount_bucketing(AmountBucketing::SignificantDigits(3));
+
+    cache.find_routers(common::params(123_456)).await.unwrap();
+    cache.find_routers(common::params(123_999)).await.unwrap();
+
+    assert_eq!(mock.call_count(), 1);
+    assert_eq!(mock.calls()[0].params.amount, U256::from(123_000u64));
+}
+
+#[tokio::test]
+async fn errors_are_shared_with_waiters_but_not_cached() {
+    let mock = mock(Duration::from_millis(50));
+    mock.inject(MockResponse::http_error(503, "Service Unavailable"));
+    let cache = QuoteCache::new(mock.clone(), Duration::from_secs(60));
+
+    let results = concurrently(&cache, 3).await;
+
+    assert_eq!(mock.call_count(), 1);
+    // 最後に結果を受け取った呼び出し元以外は同じエラーを共有する
+    let errors: Vec<AggregatorError> = results.into_iter().map(Result::unwrap_err).collect();
+    for error in &errors {
+        let inner = match error {
+            AggregatorError::Shared(inner) => inner.as_ref(),
+            error => error,
+        };
+        assert!(matches!(
+            inner,
+            AggregatorError::HttpError { status: 503, .. }
+        ));
+    }
+    assert!(errors
+        .iter()
+        .any(|error| matches!(error, AggregatorError::Shared(_))));
+    assert_eq!(cache.stats().entries, 0);
+
+    assert!(cache
+        .find_routers(common::params(1_000))
+        .await
+        .unwrap()
+        .is_some());
+    assert_eq!(mock.call_count(), 2);
+}
+
+#[tokio::test]
+async fn cancelled_request_does_not_leave_an_in_flight_entry() {
+    let mock = mock(Duration::from_millis(50));
+    let cache = QuoteCache::new(mock.clone(), Duration::from_secs(60));
+
+    let cancelled = tokio::time::timeout(
+        Duration::from_millis(10),
+        cache.find_routers(common::params(1_000)),
+    )
+    .await;
+    assert!(cancelled.is_err());
+
+    // 後続の呼び出し元は合流せず、新しくAPIを呼び出す
+    cache.find_routers(common::params(1_000)).await.unwrap();
+    assert_eq!(mock.call_count(), 2);
+    assert_eq!(
+        cache.stats(),
+        CacheStats {
+            hits: 0,
+            misses: 2,
+            coalesced: 0,
+            entries: 1,
+        }
+    );
+}
+
+#[tokio::test]
+async fn waiter_takes_over_when_the_first_caller_is_cancelled() {
+    let mock = mock(Duration::from_millis(50));
+    let cache = QuoteCache::new(mock.clone(), Duration::from_secs(60));
+
+    let spawn = |delay| {
+        let cache = cache.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(delay).await;
+            cache.find_routers(common::params(1_000)).await
+        })
+    };
+    let first = spawn(Duration::ZERO);
+    let waiter = spawn(Duration::from_millis(10));
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    first.abort();
+
+    // 代わりに実行している呼び出し元にも、後から到着した呼び出し元が合流する
+    let late = spawn(Duration::from_millis(10));
+    waiter.await.unwrap().unwrap();
+    late.await.unwrap().unwrap();
+
+    assert_eq!(mock.call_count(), 2);
+    assert_eq!(
+        cache.stats(),
+        CacheStats {
+            hits: 0,
+            misses: 2,
+            coalesced: 1,
+            entries: 1,
+        }
+    );
+}
@@ -0,0 +1,86 @@
+//! カセットの記録と再生の結合テスト
+
+mod common;
+
+use std::path::PathBuf;
+
+use cetus_aggregator_rust::mock_server::MockServer;
+use cetus_aggregator_rust::{AggregatorClient, AggregatorClientTrait, AggregatorError, Cassette};
+use primitive_types::U256;
+
+/// テストごとに別のカセットファイルのパス
+fn cassette_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!(
+        "cetus-aggregator-cassette-{}-{}.json",
+        std::process::id(),
+        name
+    ));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+async fn record(server: &MockServer, path: &PathBuf) -> AggregatorClient {
+    let client = AggregatorClient::builder()
+        .endpoint(server.url())
+        .cassette(Cassette::record(path))
+        .build()
+        .unwrap();
+    for amount in [1_000, 2_000] {
+        client.find_routers(common::params(amount)).await.unwrap();
+    }
+    client
+}
+
+#[tokio::test]
+async fn records_on_save_and_replays_without_network() {
+    let path = cassette_path("save");
+    let server = MockServer::start().await.unwrap();
+    server.respond_with(common::doubling);
+
+    let client = record(&server, &path).await;
+    // 保存するまでファイルには書き込まない
+    assert!(!path.exists());
+    client.cassette().unwrap().save().unwrap();
+    assert!(path.exists());
+    drop(server);
+
+    let replay = AggregatorClient::builder()
+        .endpoint("http://127.0.0.1:9")
+        .cassette(Cassette::replay(&path).unwrap())
+        .build()
+        .unwrap();
+    let data = replay
+        .find_routers(common::params(2_000))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(data.amount_out, U256::from(4_000u64));
+    assert_eq!(replay.cassette().unwrap().unplayed().len(), 1);
+
+    let error = replay
+        .find_routers(common::params(3_000))
+        .await
+        .unwrap_err();
+    assert!(matches!(error, AggregatorError::CassetteMismatch { .. }));
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+async fn dropping_the_client_writes_the_cassette() {
+    let path = cassette_path("drop");
+    let server = MockServer::start().await.unwrap();
+    server.respond_with(common::doubling);
+
+    let client = record(&server, &path).await;
+    let clone = client.clone();
+    drop(client);
+    // クローンが残っている間は書き込まない
+    assert!(!path.exists());
+    drop(clone);
+
+    let cassette = Cassette::replay(&path).unwrap();
+    assert_eq!(cassette.interactions().len(), 2);
+
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,231 @@
+//! サーキットブレーカーの結合テスト
+
+mod common;
+
+use std::time::Duration;
+
+use cetus_aggregator_rust::mock_server::{MockServer, ServerResponse};
+use cetus_aggregator_rust::{
+    AggregatorClient, AggregatorClientTrait, AggregatorError, AggregatorServerErrorCode,
+    CircuitBreaker, CircuitState,
+};
+
+const OPEN_DURATION: Duration = Duration::from_millis(100);
+
+fn breaker() -> CircuitBreaker {
+    CircuitBreaker {
+        failure_threshold: 2,
+        timeout_rate_threshold: None,
+        window_size: 20,
+        open_duration: OPEN_DURATION,
+    }
+}
+
+/// 503を返し続けるサーバーと、2回の失敗で回路が開いたクライアント
+async fn opened() -> (MockServer, AggregatorClient) {
+    let server = MockServer::start().await.unwrap();
+    server.respond(ServerResponse::status(503, "Service Unavailable"));
+    let client = AggregatorClient::builder()
+        .endpoint(server.url())
+        .circuit_breaker(breaker())
+        .build()
+        .unwrap();
+
+    for _ in 0..2 {
+        let error = client
+            .find_routers(common::params(1_000))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            AggregatorError::HttpError { status: 503, .. }
+        ));
+    }
+    (server, client)
+}
+
+fn circuit(client: &AggregatorClient) -> CircuitState {
+    client.endpoint_status()[0].circuit
+}
+
+#[tokio::test]
+async fn opens_after_consecutive_failures_and_fails_fast() {
+    let (server, client) = opened().await;
+    assert!(matches!(circuit(&client), CircuitState::Open { .. }));
+
+    let error = client
+        .find_routers(common::params(1_000))
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(error, AggregatorError::CircuitOpen { retry_in, .. } if retry_in <= OPEN_DURATION)
+    );
+    assert_eq!(server.request_count(), 2);
+}
+
+#[tokio::test]
+async fn successful_probe_closes_the_circuit() {
+    let (server, client) = opened().await;
+    server.respond(ServerResponse::success(common::swap(1_000, 3)));
+
+    tokio::time::sleep(OPEN_DURATION).await;
+    client.find_routers(common::params(1_000)).await.unwrap();
+    assert_eq!(circuit(&client), CircuitState::Closed);
+
+    client.find_routers(common::params(1_000)).await.unwrap();
+    assert_eq!(server.request_count(), 4);
+}
+
+#[tokio::test]
+async fn failed_probe_reopens_the_circuit() {
+    let (server, client) = opened().await;
+
+    tokio::time::sleep(OPEN_DURATION).await;
+    let error = client
+        .find_routers(common::params(1_000))
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        AggregatorError::HttpError { status: 503, .. }
+    ));
+    assert!(matches!(circuit(&client), CircuitState::Open { .. }));
+
+    let error = client
+        .find_routers(common::params(1_000))
+        .await
+        .unwrap_err();
+    assert!(matches!(error, AggregatorError::CircuitOpen { .. }));
+    assert_eq!(server.request_count(), 3);
+}
+
+#[tokio::test]
+async fn local_error_releases_the_probe_without_closing() {
+    let (server, client) = opened().await;
+    server.respond(ServerResponse::malformed_json());
+
+    tokio::time::sleep(OPEN_DURATION).await;
+    let error = client
+        .find_routers(common::params(1_000))
+        .await
+        .unwrap_err();
+    assert!(matches!(error, AggregatorError::DecodeError { .. }));
+    assert_eq!(
+        circuit(&client),
+        CircuitState::Open {
+            remaining: Duration::ZERO
+        }
+    );
+
+    // 次のリクエストを改めて試験的に送信し、サーバーの確定的な応答で回路を閉じる
+    server.respond(ServerResponse::status(400, "Bad Request"));
+    let error = client
+        .find_routers(common::params(1_000))
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        AggregatorError::HttpError { status: 400, .. }
+    ));
+    assert_eq!(circuit(&client), CircuitState::Closed);
+    assert_eq!(server.request_count(), 4);
+}
+
+#[tokio::test]
+async fn honeypot_answer_to_the_probe_closes_the_circuit() {
+    let (server, client) = opened().await;
+    server.respond(ServerResponse::router_error(
+        AggregatorServerErrorCode::HoneyPot,
+    ));
+
+    tokio::time::sleep(OPEN_DURATION).await;
+    let error = client
+        .find_routers(common::params(1_000))
+        .await
+        .unwrap_err();
+    assert!(matches!(error, AggregatorError::HoneyPot { .. }));
+    assert_eq!(circuit(&client), CircuitState::Closed);
+}
+
+#[tokio::test]
+async fn cancelled_probe_is_released_immediately() {
+    let (server, client) = opened().await;
+    server.respond(
+        ServerResponse::success(common::swap(1_000, 3)).delayed(Duration::from_millis(200)),
+    );
+
+    tokio::time::sleep(OPEN_DURATION).await;
+    let cancelled = tokio::time::timeout(
+        Duration::from_millis(20),
+        client.find_routers(common::params(1_000)),
+    )
+    .await;
+    assert!(cancelled.is_err());
+    assert_eq!(
+        circuit(&client),
+        CircuitState::Open {
+            remaining: Duration::ZERO
+        }
+    );
+
+    // 次のリクエストは`open_duration`を待たずに試験的に送信できる
+    server.respond(ServerResponse::success(common::swap(1_000, 3)));
+    client.find_routers(common::params(1_000)).await.unwrap();
+    assert_eq!(circuit(&client), CircuitState::Closed);
+}
+
+#[tokio::test]
+async fn half_open_circuit_sends_a_single_probe() {
+    let (server, client) = opened().await;
+    server.respond(
+        ServerResponse::success(common::swap(1_000, 3)).delayed(Duration::from_millis(50)),
+    );
+
+    tokio::time::sleep(OPEN_DURATION).await;
+    let probe = tokio::spawn({
+        let client = client.clone();
+        async move { client.find_routers(common::params(1_000)).await }
+    });
+    while circuit(&client) != CircuitState::HalfOpen {
+        tokio::time::sleep(Duration::from_millis(1)).await;
+    }
+
+    // 試験的な送信の結果を待っている間は送信しない
+    let error = client
+        .find_routers(common::params(1_000))
+        .await
+        .unwrap_err();
+    assert!(matches!(error, AggregatorError::CircuitOpen { .. }));
+
+    probe.await.unwrap().unwrap();
+    assert_eq!(circuit(&client), CircuitState::Closed);
+    assert_eq!(server.request_count(), 3);
+}
+
+#[tokio::test]
+async fn opens_when_timeout_rate_reaches_threshold() {
+    let server = MockServer::start().await.unwrap();
+    server.respond(
+        ServerResponse::success(common::swap(1_000, 3)).delayed(Duration::from_millis(200)),
+    );
+    let client = AggregatorClient::builder()
+        .endpoint(server.url())
+        .read_timeout(Duration::from_millis(20))
+        .circuit_breaker(CircuitBreaker {
+            failure_threshold: 100,
+            timeout_rate_threshold: Some(0.5),
+            window_size: 2,
+            open_duration: OPEN_DURATION,
+        })
+        .build()
+        .unwrap();
+
+    for _ in 0..2 {
+        let error = client
+            .find_routers(common::params(1_000))
+            .await
+            .unwrap_err();
+        assert!(matches!(error, AggregatorError::RequestError(ref e) if e.is_timeout()));
+    }
+    assert!(matches!(circuit(&client), CircuitState::Open { .. }));
+}
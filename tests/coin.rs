@@ -0,0 +1,49 @@
+//! コインタイプの解析の結合テスト
+
+use cetus_aggregator_rust::coin::MAX_TYPE_ARGUMENT_DEPTH;
+use cetus_aggregator_rust::{AggregatorError, CoinType};
+
+fn nested(depth: usize) -> String {
+    format!(
+        "0x2::coin::Coin<{}u64{}>",
+        "vector<".repeat(depth - 1),
+        ">".repeat(depth - 1)
+    )
+}
+
+fn reason(error: AggregatorError) -> String {
+    match error {
+        AggregatorError::InputError(message) => message,
+        other => panic!("入力エラーではありません: {:?}", other),
+    }
+}
+
+#[test]
+fn accepts_type_arguments_up_to_max_depth() {
+    let coin: CoinType = nested(MAX_TYPE_ARGUMENT_DEPTH).parse().unwrap();
+    assert_eq!(
+        coin.to_string().matches("vector<").count(),
+        MAX_TYPE_ARGUMENT_DEPTH - 1
+    );
+}
+
+#[test]
+fn rejects_deeply_nested_type_arguments() {
+    let error = nested(MAX_TYPE_ARGUMENT_DEPTH + 1)
+        .parse::<CoinType>()
+        .unwrap_err();
+    assert!(reason(error).contains("型引数の入れ子が上限（16）を超えています"));
+
+    // 再帰の上限により、極端に深い入力でもスタックを使い果たさずにエラーになる
+    let deep = format!("0x2::coin::Coin<{}", "0x2::coin::Coin<".repeat(100_000));
+    assert!(deep.parse::<CoinType>().is_err());
+}
+
+#[test]
+fn error_positions_count_characters() {
+    // 全角スペース（3バイト）の後の位置を文字数で報告する
+    let error = "0x2::coin::Coin<\u{3000}!>"
+        .parse::<CoinType>()
+        .unwrap_err();
+    assert!(reason(error).contains("18文字目にアドレスが必要です"));
+}
@@ -0,0 +1,42 @@
+//! 結合テストで共通して使用するヘルパー
+
+#![allow(dead_code)]
+
+use cetus_aggregator_rust::fixture::RouterDataBuilder;
+use cetus_aggregator_rust::mock_server::ServerResponse;
+use cetus_aggregator_rust::{CoinType, FindRouterParams, RouterData};
+use primitive_types::U256;
+
+/// 交換元のコイン
+pub fn sui() -> CoinType {
+    CoinType::sui()
+}
+
+/// 交換先のコイン
+pub fn usdc() -> CoinType {
+    "0x5d4b302506645c37ff133b98c4b50a5ae14841659738d6d733d59d0d217a93bf::coin::COIN"
+        .parse()
+        .unwrap()
+}
+
+/// SUIからUSDCへの入力量ベースのパラメータ
+pub fn params(amount: u64) -> FindRouterParams {
+    FindRouterParams {
+        from: sui(),
+        target: usdc(),
+        amount: U256::from(amount),
+        by_amount_in: true,
+        ..Default::default()
+    }
+}
+
+/// SUIからUSDCへの1ホップのルート検索結果
+pub fn swap(amount_in: u64, amount_out: u64) -> RouterData {
+    RouterDataBuilder::swap(sui(), usdc(), amount_in, amount_out).build()
+}
+
+/// 入力量の2倍を出力量として返すレスポンス
+pub fn doubling(request: &cetus_aggregator_rust::mock_server::ReceivedRequest) -> ServerResponse {
+    let amount: u64 = request.param("amount").unwrap().parse().unwrap();
+    ServerResponse::success(swap(amount, amount * 2))
+}
@@ -0,0 +1,168 @@
+//! 見積もり曲線の結合テスト
+
+mod common;
+
+use cetus_aggregator_rust::fixture::{PathBuilder, RouteBuilder, RouterDataBuilder};
+use cetus_aggregator_rust::mock_server::{MockServer, ReceivedRequest, ServerResponse};
+use cetus_aggregator_rust::{AggregatorError, AggregatorServerErrorCode, Provider, Ratio};
+use primitive_types::U256;
+
+/// 初期価格1で、入力量に比例する価格インパクト（入力量/10^6）を持つ見積もりを返す
+///
+/// 入力量が50,000を超える場合は`data.error`で流動性不足を返します。
+fn impact_curve(request: &ReceivedRequest) -> ServerResponse {
+    let amount: u64 = request.param("amount").unwrap().parse().unwrap();
+    if amount > 50_000 {
+        return ServerResponse::router_error(AggregatorServerErrorCode::InsufficientLiquidity);
+    }
+    let amount_out = amount - amount * amount / 1_000_000;
+    let route = RouteBuilder::new()
+        .hop(
+            PathBuilder::new(
+                Provider::Cetus,
+                common::sui(),
+                common::usdc(),
+                amount,
+                amount_out,
+            )
+            .id("0xpool")
+            .build(),
+        )
+        .initial_price(Ratio::from_integer(U256::one()))
+        .build();
+    ServerResponse::success(RouterDataBuilder::new().route(route).build())
+}
+
+async fn server() -> MockServer {
+    let server = MockServer::start().await.unwrap();
+    server.respond_with(impact_curve);
+    server
+}
+
+fn amounts(amounts: &[u64]) -> Vec<U256> {
+    amounts.iter().map(|amount| U256::from(*amount)).collect()
+}
+
+#[tokio::test]
+async fn builds_sorted_curve_and_collects_amounts_without_route() {
+    let server = server().await;
+    let curve = server
+        .client()
+        .quote_curve(
+            common::sui(),
+            common::usdc(),
+            amounts(&[100_000, 10_000, 1_000, 1_000, 0]),
+        )
+        .await
+        .unwrap();
+
+    let inputs: Vec<U256> = curve.points.iter().map(|point| point.amount_in).collect();
+    assert_eq!(inputs, amounts(&[1_000, 10_000]));
+    assert_eq!(curve.no_route, amounts(&[100_000]));
+
+    assert_eq!(curve.points[0].amount_out, U256::from(999u64));
+    assert_eq!(curve.points[0].impact_bps(), Some(U256::from(10u64)));
+    assert_eq!(curve.points[0].marginal_price, None);
+    assert_eq!(curve.points[1].impact_bps(), Some(U256::from(100u64)));
+    assert_eq!(
+        curve.points[1].marginal_price,
+        Ratio::new(U256::from(9_900u64 - 999), U256::from(9_000u64))
+    );
+    assert!(!curve.points[1].route_changed);
+}
+
+#[tokio::test]
+async fn other_errors_fail_the_curve() {
+    let server = server().await;
+    server.enqueue(ServerResponse::status(400, "Bad Request"));
+
+    let error = server
+        .client()
+        .quote_curve(common::sui(), common::usdc(), amounts(&[1_000]))
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        AggregatorError::HttpError { status: 400, .. }
+    ));
+}
+
+#[tokio::test]
+async fn interpolates_between_points() {
+    let server = server().await;
+    let curve = server
+        .client()
+        .quote_curve(common::sui(), common::usdc(), amounts(&[1_000, 10_000]))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        curve.interpolate(U256::from(10_000u64)),
+        Some(U256::from(9_900u64))
+    );
+    // 999 + (9,900 - 999) * 4,500 / 9,000（切り捨て）
+    assert_eq!(
+        curve.interpolate(U256::from(5_500u64)),
+        Some(U256::from(5_449u64))
+    );
+    // 最初の点より小さい入力量は原点との間で補間
+    assert_eq!(
+        curve.interpolate(U256::from(500u64)),
+        Some(U256::from(499u64))
+    );
+    assert_eq!(curve.interpolate(U256::from(10_001u64)), None);
+}
+
+#[tokio::test]
+async fn searches_largest_amount_within_impact() {
+    let server = server().await;
+    let client = server.client();
+    let curve = client
+        .quote_curve(common::sui(), common::usdc(), amounts(&[1_000, 10_000]))
+        .await
+        .unwrap();
+    assert_eq!(
+        curve.max_within_impact(50).unwrap().amount_in,
+        U256::from(1_000u64)
+    );
+
+    let point = client
+        .max_amount_within_impact(&curve, 50, 20)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(point.within_impact(50));
+    assert!(point.amount_in > U256::from(4_900u64) && point.amount_in <= U256::from(5_100u64));
+    // 探索点の間隔が1になると反復回数に達する前に終了する
+    assert!(server.request_count() < 2 + 20);
+
+    // 反復回数を超えて検索しない
+    let before = server.request_count();
+    client
+        .max_amount_within_impact(&curve, 50, 3)
+        .await
+        .unwrap();
+    assert_eq!(server.request_count(), before + 3);
+}
+
+#[tokio::test]
+async fn search_treats_no_route_as_over_the_limit() {
+    let server = server().await;
+    let client = server.client();
+    let curve = client
+        .quote_curve(common::sui(), common::usdc(), amounts(&[1_000, 10_000]))
+        .await
+        .unwrap();
+
+    // 最初の探索点（5,500）でルートなしを返す
+    server.enqueue(ServerResponse::server_error(
+        AggregatorServerErrorCode::NoRouter,
+    ));
+    let point = client
+        .max_amount_within_impact(&curve, 50, 20)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(point.within_impact(50));
+    assert!(point.amount_in > U256::from(1_000u64) && point.amount_in < U256::from(5_500u64));
+}
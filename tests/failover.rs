@@ -0,0 +1,181 @@
+//! 複数エンドポイントのフェイルオーバーとヘッジリクエストの結合テスト
+
+mod common;
+
+use std::time::{Duration, Instant};
+
+use cetus_aggregator_rust::mock_server::{MockServer, ServerResponse};
+use cetus_aggregator_rust::{AggregatorClient, AggregatorClientTrait, AggregatorError};
+
+async fn servers() -> (MockServer, MockServer) {
+    (
+        MockServer::start().await.unwrap(),
+        MockServer::start().await.unwrap(),
+    )
+}
+
+#[tokio::test]
+async fn fails_over_to_next_endpoint_on_retryable_error() {
+    let (primary, mirror) = servers().await;
+    primary.respond(ServerResponse::status(503, "unavailable"));
+    mirror.respond(ServerResponse::success(common::swap(10, 20)));
+
+    let client = AggregatorClient::builder()
+        .endpoints([primary.url(), mirror.url()])
+        .build()
+        .unwrap();
+    let data = client
+        .find_routers(common::params(10))
+        .await
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(data.amount_out, 20u64.into());
+    assert_eq!(primary.request_count(), 1);
+    assert_eq!(mirror.request_count(), 1);
+    let status = client.endpoint_status();
+    assert_eq!(status[0].failures, 1);
+    assert_eq!(status[1].successes, 1);
+}
+
+#[tokio::test]
+async fn does_not_fail_over_on_client_error() {
+    let (primary, mirror) = servers().await;
+    primary.respond(ServerResponse::status(400, "bad request"));
+    mirror.respond(ServerResponse::success(common::swap(10, 20)));
+
+    let client = AggregatorClient::builder()
+        .endpoints([primary.url(), mirror.url()])
+        .build()
+        .unwrap();
+    let error = client.find_routers(common::params(10)).await.unwrap_err();
+
+    assert!(matches!(
+        error,
+        AggregatorError::HttpError { status: 400, .. }
+    ));
+    assert_eq!(mirror.request_count(), 0);
+}
+
+#[tokio::test]
+async fn returns_last_error_when_every_endpoint_fails() {
+    let (primary, mirror) = servers().await;
+    primary.respond(ServerResponse::status(503, "unavailable"));
+    mirror.respond(ServerResponse::status(502, "bad gateway"));
+
+    let client = AggregatorClient::builder()
+        .endpoints([primary.url(), mirror.url()])
+        .build()
+        .unwrap();
+    let error = client.find_routers(common::params(10)).await.unwrap_err();
+
+    assert!(matches!(
+        error,
+        AggregatorError::HttpError { status: 502, .. }
+    ));
+}
+
+#[tokio::test]
+async fn unhealthy_endpoint_recovers_after_interval() {
+    let (primary, mirror) = servers().await;
+    primary
+        .enqueue(ServerResponse::status(503, "unavailable"))
+        .respond(ServerResponse::success(common::swap(10, 11)));
+    mirror.respond(ServerResponse::success(common::swap(10, 22)));
+
+    let client = AggregatorClient::builder()
+        .endpoints([primary.url(), mirror.url()])
+        .unhealthy_threshold(1)
+        .recovery_interval(Duration::from_millis(200))
+        .build()
+        .unwrap();
+
+    // 最初の失敗で不健全になり、回復間隔の間はミラーが先に使用される
+    client.find_routers(common::params(10)).await.unwrap();
+    let data = client
+        .find_routers(common::params(10))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(data.amount_out, 22u64.into());
+    assert_eq!(primary.request_count(), 1);
+    assert!(!client.endpoint_status()[0].healthy);
+
+    // 回復間隔が経過すると再び最優先のエンドポイントが試される
+    tokio::time::sleep(Duration::from_millis(250)).await;
+    assert!(client.endpoint_status()[0].healthy);
+    let data = client
+        .find_routers(common::params(10))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(data.amount_out, 11u64.into());
+    assert_eq!(client.endpoint_status()[0].consecutive_failures, 0);
+}
+
+#[tokio::test]
+async fn hedged_request_uses_faster_endpoint() {
+    let (primary, mirror) = servers().await;
+    primary
+        .respond(ServerResponse::success(common::swap(10, 11)).delayed(Duration::from_millis(500)));
+    mirror.respond(ServerResponse::success(common::swap(10, 22)));
+
+    let client = AggregatorClient::builder()
+        .endpoints([primary.url(), mirror.url()])
+        .hedged(Duration::from_millis(50))
+        .build()
+        .unwrap();
+    let started = Instant::now();
+    let data = client
+        .find_routers(common::params(10))
+        .await
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(data.amount_out, 22u64.into());
+    assert!(started.elapsed() < Duration::from_millis(400));
+    assert_eq!(mirror.request_count(), 1);
+}
+
+#[tokio::test]
+async fn hedged_request_is_not_sent_when_primary_is_fast() {
+    let (primary, mirror) = servers().await;
+    primary.respond(ServerResponse::success(common::swap(10, 11)));
+    mirror.respond(ServerResponse::success(common::swap(10, 22)));
+
+    let client = AggregatorClient::builder()
+        .endpoints([primary.url(), mirror.url()])
+        .hedged(Duration::from_millis(500))
+        .build()
+        .unwrap();
+    let data = client
+        .find_routers(common::params(10))
+        .await
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(data.amount_out, 11u64.into());
+    assert_eq!(mirror.request_count(), 0);
+}
+
+#[tokio::test]
+async fn hedged_request_falls_back_immediately_when_primary_fails() {
+    let (primary, mirror) = servers().await;
+    primary.respond(ServerResponse::status(503, "unavailable"));
+    mirror.respond(ServerResponse::success(common::swap(10, 22)));
+
+    let client = AggregatorClient::builder()
+        .endpoints([primary.url(), mirror.url()])
+        .hedged(Duration::from_secs(5))
+        .build()
+        .unwrap();
+    let started = Instant::now();
+    let data = client
+        .find_routers(common::params(10))
+        .await
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(data.amount_out, 22u64.into());
+    assert!(started.elapsed() < Duration::from_secs(1));
+}
@@ -0,0 +1,155 @@
+//! レート制限と同時実行数の制限の結合テスト
+
+mod common;
+
+use std::time::{Duration, Instant};
+
+use cetus_aggregator_rust::mock_server::{MockServer, ServerResponse};
+use cetus_aggregator_rust::{AggregatorClient, LimiterStatus, Priority, RateLimit};
+use tokio::task::JoinHandle;
+
+/// すべてのリクエストに`delay`だけ遅れて応答するサーバー
+async fn slow_server(delay: Duration) -> MockServer {
+    let server = MockServer::start().await.unwrap();
+    server.respond_with(move |request| common::doubling(request).delayed(delay));
+    server
+}
+
+fn spawn(client: &AggregatorClient, amount: u64, priority: Priority) -> JoinHandle<()> {
+    let client = client.clone();
+    tokio::spawn(async move {
+        client
+            .find_routers_with_priority(common::params(amount), priority)
+            .await
+            .unwrap();
+    })
+}
+
+fn status(client: &AggregatorClient) -> LimiterStatus {
+    client.limiter_status().unwrap()
+}
+
+/// 送信中が`in_flight`件、待機列が`queued`件になるまで待機
+async fn wait_for(client: &AggregatorClient, in_flight: usize, queued: usize) {
+    loop {
+        let status = status(client);
+        if status.in_flight == in_flight && status.queued == queued {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(1)).await;
+    }
+}
+
+fn received_amounts(server: &MockServer) -> Vec<String> {
+    server
+        .requests()
+        .iter()
+        .map(|request| request.param("amount").unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn queued_requests_are_sent_by_priority_then_arrival() {
+    let server = slow_server(Duration::from_millis(50)).await;
+    let client = AggregatorClient::builder()
+        .endpoint(server.url())
+        .max_in_flight(1)
+        .build()
+        .unwrap();
+
+    let first = spawn(&client, 1, Priority::Normal);
+    wait_for(&client, 1, 0).await;
+    let mut tasks = vec![first];
+    for (amount, priority) in [
+        (2, Priority::Low),
+        (3, Priority::Normal),
+        (4, Priority::High),
+        (5, Priority::Normal),
+    ] {
+        tasks.push(spawn(&client, amount, priority));
+        wait_for(&client, 1, tasks.len() - 1).await;
+    }
+    assert_eq!(
+        status(&client),
+        LimiterStatus {
+            in_flight: 1,
+            queued: 4,
+            available_tokens: None,
+        }
+    );
+
+    for task in tasks {
+        task.await.unwrap();
+    }
+    assert_eq!(received_amounts(&server), ["1", "4", "3", "5", "2"]);
+    assert_eq!(status(&client).in_flight, 0);
+}
+
+#[tokio::test]
+async fn cancelled_requests_leave_the_queue() {
+    let server = slow_server(Duration::from_millis(50)).await;
+    let client = AggregatorClient::builder()
+        .endpoint(server.url())
+        .max_in_flight(1)
+        .build()
+        .unwrap();
+
+    let first = spawn(&client, 1, Priority::Normal);
+    wait_for(&client, 1, 0).await;
+    let cancelled = spawn(&client, 2, Priority::High);
+    wait_for(&client, 1, 1).await;
+    let last = spawn(&client, 3, Priority::Low);
+    wait_for(&client, 1, 2).await;
+
+    cancelled.abort();
+    wait_for(&client, 1, 1).await;
+
+    first.await.unwrap();
+    last.await.unwrap();
+    assert_eq!(received_amounts(&server), ["1", "3"]);
+}
+
+#[tokio::test]
+async fn max_in_flight_bounds_concurrent_requests() {
+    let delay = Duration::from_millis(50);
+    let server = slow_server(delay).await;
+    let client = AggregatorClient::builder()
+        .endpoint(server.url())
+        .max_in_flight(2)
+        .build()
+        .unwrap();
+
+    let started = Instant::now();
+    let tasks: Vec<_> = (1..=4)
+        .map(|amount| spawn(&client, amount, Priority::Normal))
+        .collect();
+    wait_for(&client, 2, 2).await;
+
+    for task in tasks {
+        task.await.unwrap();
+    }
+    assert!(started.elapsed() >= delay * 2);
+}
+
+#[tokio::test]
+async fn rate_limit_spaces_requests_after_the_burst() {
+    let server = MockServer::start().await.unwrap();
+    server.respond(ServerResponse::success(common::swap(1_000, 3)));
+    let client = AggregatorClient::builder()
+        .endpoint(server.url())
+        .rate_limit(RateLimit::per_second(20).with_burst(2))
+        .build()
+        .unwrap();
+
+    let started = Instant::now();
+    for _ in 0..4 {
+        client
+            .find_routers_with_priority(common::params(1_000), Priority::Normal)
+            .await
+            .unwrap();
+    }
+
+    // 容量の2件はすぐに送信し、残りの2件は50msごとに送信する
+    assert!(started.elapsed() >= Duration::from_millis(90));
+    assert_eq!(server.request_count(), 4);
+}
@@ -0,0 +1,89 @@
+//! モッククライアントの結合テスト
+
+mod common;
+
+use cetus_aggregator_rust::fixture::RouterDataBuilder;
+use cetus_aggregator_rust::mock::{MockAggregatorClient, MockResponse};
+use cetus_aggregator_rust::mock_server::{MockServer, ServerResponse};
+use cetus_aggregator_rust::{AggregatorClientTrait, AggregatorError, AggregatorServerErrorCode};
+use primitive_types::U256;
+
+/// 同じフィクスチャをモッククライアントと実際のクライアントに返させる
+async fn both(data: cetus_aggregator_rust::RouterData) -> (AggregatorError, AggregatorError) {
+    let mock = MockAggregatorClient::new();
+    mock.fallback(MockResponse::route(data.clone()));
+    let mock_error = mock.find_routers(common::params(1_000)).await.unwrap_err();
+
+    let server = MockServer::start().await.unwrap();
+    server.respond(ServerResponse::success(data));
+    let client_error = server
+        .client()
+        .find_routers(common::params(1_000))
+        .await
+        .unwrap_err();
+
+    (mock_error, client_error)
+}
+
+#[tokio::test]
+async fn router_error_in_fixture_is_returned_as_error() {
+    let data = RouterDataBuilder::swap(common::sui(), common::usdc(), 1_000u64, 0u64)
+        .error(AggregatorServerErrorCode::NoRouter as u32, "no router")
+        .build();
+
+    let (mock_error, client_error) = both(data).await;
+    assert!(matches!(mock_error, AggregatorError::NoRouter { .. }));
+    assert_eq!(mock_error.id(), client_error.id());
+}
+
+#[tokio::test]
+async fn insufficient_liquidity_in_fixture_is_returned_as_error() {
+    let data = RouterDataBuilder::swap(common::sui(), common::usdc(), 1_000u64, 0u64)
+        .insufficient_liquidity(true)
+        .build();
+
+    let (mock_error, client_error) = both(data).await;
+    assert!(matches!(
+        mock_error,
+        AggregatorError::InsufficientLiquidity { .. }
+    ));
+    assert_eq!(mock_error.id(), client_error.id());
+}
+
+#[tokio::test]
+async fn invalid_params_do_not_consume_scripted_responses() {
+    let mock = MockAggregatorClient::new();
+    mock.respond(
+        common::sui(),
+        common::usdc(),
+        MockResponse::route(common::swap(1_000, 1)),
+    )
+    .respond(
+        common::sui(),
+        common::usdc(),
+        MockResponse::route(common::swap(1_000, 2)),
+    );
+
+    let error = mock.find_routers(common::params(0)).await.unwrap_err();
+    assert!(matches!(error, AggregatorError::InputError(_)));
+
+    let data = mock
+        .find_routers(common::params(1_000))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(data.amount_out, U256::from(1u64));
+    assert_eq!(mock.call_count(), 2);
+}
+
+#[test]
+fn fixture_pool_ids_depend_only_on_the_pool() {
+    let forward = common::swap(1_000, 2_000);
+    let reverse =
+        RouterDataBuilder::swap(common::usdc(), common::sui(), 2_000u64, 1_000u64).build();
+    let id = |data: &cetus_aggregator_rust::RouterData| data.routes[0].path[0].id.clone();
+
+    // 何度作成しても、逆向きの交換でも同じプールを指す
+    assert_eq!(id(&forward), id(&common::swap(1_000, 2_000)));
+    assert_eq!(id(&forward), id(&reverse));
+}
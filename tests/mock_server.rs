@@ -0,0 +1,194 @@
+//! モックサーバーの結合テスト
+
+mod common;
+
+use std::time::Duration;
+
+use cetus_aggregator_rust::mock_server::{MockServer, ServerResponse};
+use cetus_aggregator_rust::{
+    AggregatorClient, AggregatorClientTrait, AggregatorError, AggregatorServerErrorCode,
+};
+use primitive_types::U256;
+use serde_json::Value;
+
+/// クライアントを介さずにリクエストを送信し、ステータスコードと本文を取得
+async fn fetch(server: &MockServer) -> (u16, String) {
+    let response = reqwest::get(format!("{}/find_routes", server.url()))
+        .await
+        .unwrap();
+    let status = response.status().as_u16();
+    (status, response.text().await.unwrap())
+}
+
+fn json(body: &str) -> Value {
+    serde_json::from_str(body).unwrap()
+}
+
+#[tokio::test]
+async fn responds_to_custom_api_path() {
+    let server = MockServer::start().await.unwrap();
+    server.respond(ServerResponse::success(common::swap(1_000, 3)));
+
+    let client = AggregatorClient::builder()
+        .endpoint(format!("{}/router_v3", server.url()))
+        .api_path("custom_routes")
+        .build()
+        .unwrap();
+    let data = client
+        .find_routers(common::params(1_000))
+        .await
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(data.amount_out, U256::from(3u64));
+    let requests = server.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].path, "/router_v3/custom_routes");
+}
+
+#[tokio::test]
+async fn server_error_returns_code_in_body() {
+    let server = MockServer::start().await.unwrap();
+    server.respond(ServerResponse::server_error(
+        AggregatorServerErrorCode::NumberTooLarge,
+    ));
+
+    let (status, body) = fetch(&server).await;
+    assert_eq!(status, 200);
+    let body = json(&body);
+    assert_eq!(body["code"], 10001);
+    assert!(body["data"].is_null());
+
+    let error = server
+        .client()
+        .find_routers(common::params(1_000))
+        .await
+        .unwrap_err();
+    assert!(matches!(error, AggregatorError::NumberTooLarge { .. }));
+}
+
+#[tokio::test]
+async fn unknown_error_code_carries_request_context() {
+    let server = MockServer::start().await.unwrap();
+    server.respond(ServerResponse::api_error(19999, "unknown"));
+
+    let error = server
+        .client()
+        .find_routers(common::params(1_000))
+        .await
+        .unwrap_err();
+    let AggregatorError::ApiError {
+        code,
+        ref message,
+        ref context,
+    } = error
+    else {
+        panic!("APIエラーではありません: {:?}", error);
+    };
+    assert_eq!((code, message.as_str()), (19999, "unknown"));
+    assert_eq!(context.amount, U256::from(1_000u64));
+    assert_eq!(context.endpoint, server.url());
+    assert_eq!(error.request_context(), Some(&**context));
+}
+
+#[tokio::test]
+async fn router_error_returns_code_in_data() {
+    let server = MockServer::start().await.unwrap();
+    server.respond(ServerResponse::router_error(
+        AggregatorServerErrorCode::InsufficientLiquidity,
+    ));
+
+    let (status, body) = fetch(&server).await;
+    assert_eq!(status, 200);
+    let body = json(&body);
+    assert_eq!(body["code"], 200);
+    assert_eq!(body["data"]["error"]["code"], 10003);
+
+    let error = server
+        .client()
+        .find_routers(common::params(1_000))
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        AggregatorError::InsufficientLiquidity { .. }
+    ));
+}
+
+#[tokio::test]
+async fn status_returns_plain_text_with_status_code() {
+    let server = MockServer::start().await.unwrap();
+    server.respond(ServerResponse::status(503, "Service Unavailable"));
+
+    assert_eq!(
+        fetch(&server).await,
+        (503, "Service Unavailable".to_string())
+    );
+
+    let error = server
+        .client()
+        .find_routers(common::params(1_000))
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        AggregatorError::HttpError { status: 503, .. }
+    ));
+}
+
+#[tokio::test]
+async fn malformed_json_surfaces_as_decode_error() {
+    let server = MockServer::start().await.unwrap();
+    server.respond(ServerResponse::malformed_json());
+
+    let (status, body) = fetch(&server).await;
+    assert_eq!(status, 200);
+    assert!(serde_json::from_str::<Value>(&body).is_err());
+
+    let error = server
+        .client()
+        .find_routers(common::params(1_000))
+        .await
+        .unwrap_err();
+    assert!(matches!(error, AggregatorError::DecodeError { .. }));
+}
+
+#[tokio::test]
+async fn delayed_response_trips_client_timeout() {
+    let server = MockServer::start().await.unwrap();
+    server.respond(
+        ServerResponse::success(common::swap(1_000, 3)).delayed(Duration::from_millis(200)),
+    );
+    let client = AggregatorClient::builder()
+        .endpoint(server.url())
+        .read_timeout(Duration::from_millis(20))
+        .build()
+        .unwrap();
+
+    let error = client
+        .find_routers(common::params(1_000))
+        .await
+        .unwrap_err();
+    assert!(matches!(error, AggregatorError::RequestError(ref e) if e.is_timeout()));
+    assert_eq!(server.request_count(), 1);
+}
+
+#[tokio::test]
+async fn queued_responses_are_returned_before_the_responder() {
+    let server = MockServer::start().await.unwrap();
+    server
+        .respond(ServerResponse::success(common::swap(1_000, 3)))
+        .enqueue(ServerResponse::status(500, "first"))
+        .enqueue(ServerResponse::status(502, "second"));
+
+    assert_eq!(fetch(&server).await, (500, "first".to_string()));
+    assert_eq!(fetch(&server).await, (502, "second".to_string()));
+
+    // キューがなくなると`respond`の設定に戻る
+    for _ in 0..2 {
+        let (status, body) = fetch(&server).await;
+        assert_eq!(status, 200);
+        assert_eq!(json(&body)["data"]["amount_out"], "3");
+    }
+    assert_eq!(server.request_count(), 4);
+}
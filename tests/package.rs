@@ -0,0 +1,139 @@
+//! パッケージ許可リストの結合テスト
+
+mod common;
+
+use cetus_aggregator_rust::fixture::RouterDataBuilder;
+use cetus_aggregator_rust::mock_server::{MockServer, ServerResponse};
+use cetus_aggregator_rust::{
+    AggregatorClient, AggregatorClientTrait, AggregatorResponse, Network, PackageAllowlist,
+    PackageMap, PackagePolicy, PackageViolation,
+};
+use serde_json::json;
+
+/// メインネットのレスポンスに含まれるパッケージマップ
+fn mainnet_packages() -> PackageMap {
+    serde_json::from_value(json!({
+        "aggregator_v2": "0x8ae871505a80d8bf6bf9c05906cda6edfeea460c85bebe2e26a4313f5e67874a",
+        "aggregator_v2_extend": "0x8a2f7a5b20665eeccc79de3aa37c3b6c473eca233ada1e1cd4678ec07d4d4073",
+    }))
+    .unwrap()
+}
+
+/// `mainnet_packages`のIDを固定した許可リスト
+fn mainnet_allowlist() -> PackageAllowlist {
+    PackageAllowlist::from_json_str(
+        r#"{"mainnet": {
+            "aggregator_v2": ["0x8ae871505a80d8bf6bf9c05906cda6edfeea460c85bebe2e26a4313f5e67874a"],
+            "aggregator_v2_extend": ["0x8a2f7a5b20665eeccc79de3aa37c3b6c473eca233ada1e1cd4678ec07d4d4073"]
+        }}"#,
+    )
+    .unwrap()
+}
+
+#[test]
+fn verifies_packages_per_network() {
+    let packages = mainnet_packages();
+    assert_eq!(
+        mainnet_allowlist().verify(Network::Mainnet, &packages),
+        vec![]
+    );
+    assert_eq!(
+        mainnet_allowlist()
+            .verify(Network::Testnet, &packages)
+            .len(),
+        2
+    );
+    assert_eq!(
+        PackageAllowlist::new()
+            .verify(Network::Mainnet, &packages)
+            .len(),
+        2
+    );
+}
+
+#[tokio::test]
+async fn client_accepts_pinned_packages() {
+    let server = MockServer::start().await.unwrap();
+    server.respond(ServerResponse::success(
+        RouterDataBuilder::swap(common::sui(), common::usdc(), 10u64, 20u64)
+            .packages(mainnet_packages())
+            .build(),
+    ));
+    let client = AggregatorClient::builder()
+        .endpoint(server.url())
+        .package_allowlist(mainnet_allowlist(), Network::Mainnet, PackagePolicy::Reject)
+        .build()
+        .unwrap();
+
+    let data = client
+        .find_routers(common::params(10))
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(data.package_violations.is_empty());
+}
+
+#[tokio::test]
+async fn malformed_package_ids_are_flagged_instead_of_failing_decode() {
+    let response = AggregatorResponse {
+        code: 200,
+        msg: "Success".to_string(),
+        data: Some(common::swap(10, 20)),
+    };
+    let mut body = serde_json::to_value(response).unwrap();
+    body["data"]["packages"] = json!({ "aggregator_v2": "not-an-address" });
+
+    let server = MockServer::start().await.unwrap();
+    server.respond(ServerResponse::json(200, &body));
+    let client = AggregatorClient::builder()
+        .endpoint(server.url())
+        .package_allowlist(mainnet_allowlist(), Network::Mainnet, PackagePolicy::Flag)
+        .build()
+        .unwrap();
+
+    let data = client
+        .find_routers(common::params(10))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        data.package_violations,
+        vec![PackageViolation::InvalidPackageId {
+            name: "aggregator_v2".to_string(),
+            value: "not-an-address".to_string(),
+        }]
+    );
+}
+
+#[test]
+fn config_extends_or_overrides_pinned_ids() {
+    let config = PackageAllowlist::from_json_str(
+        r#"{"mainnet": {"aggregator_v2": ["0x2a"], "custom": ["0x2b"]}}"#,
+    )
+    .unwrap();
+    let upgraded: PackageMap = serde_json::from_value(json!({ "aggregator_v2": "0x2a" })).unwrap();
+
+    let extended = mainnet_allowlist().extend(config.clone());
+    assert_eq!(extended.verify(Network::Mainnet, &upgraded), vec![]);
+    assert_eq!(
+        extended.verify(Network::Mainnet, &mainnet_packages()),
+        vec![]
+    );
+
+    let overridden = mainnet_allowlist().override_with(config);
+    assert_eq!(overridden.verify(Network::Mainnet, &upgraded), vec![]);
+    let violations = overridden.verify(Network::Mainnet, &mainnet_packages());
+    assert!(matches!(
+        violations.as_slice(),
+        [PackageViolation::UnpinnedPackage { name, .. }] if name == "aggregator_v2"
+    ));
+}
+
+#[test]
+fn parse_failures_are_input_errors() {
+    let invalid = PackageAllowlist::from_json_str(r#"{"mainnet": {"aggregator_v2": ["xyz"]}}"#);
+    assert_eq!(invalid.unwrap_err().id(), "input_error");
+
+    let missing = PackageAllowlist::from_file("/nonexistent/packages.json");
+    assert_eq!(missing.unwrap_err().id(), "input_error");
+}
@@ -0,0 +1,75 @@
+//! ルート検索パラメータのビルダーの結合テスト
+//!
+//! プロセス全体のロケールを切り替えるため、ほかのテストとは別のバイナリにしています。
+
+use cetus_aggregator_rust::{
+    set_global_locale, AggregatorError, FindRouterParams, Locale, ParamLimits, SplitAlgorithm,
+};
+
+fn build_error() -> String {
+    let error = FindRouterParams::builder()
+        .from("not a coin")
+        .amount(1_000u64)
+        .build()
+        .unwrap_err();
+    match error {
+        AggregatorError::InputError(message) => message,
+        other => panic!("入力エラーではありません: {:?}", other),
+    }
+}
+
+#[test]
+fn parse_failures_are_not_reported_as_missing() {
+    for (locale, missing_from, missing_target) in [
+        (
+            Locale::Japanese,
+            "fromが指定されていません",
+            "targetが指定されていません",
+        ),
+        (Locale::English, "from is required", "target is required"),
+    ] {
+        set_global_locale(locale);
+        let message = build_error();
+        assert_eq!(message.matches("from: ").count(), 1, "{}", message);
+        assert!(!message.contains(missing_from), "{}", message);
+        assert!(message.contains(missing_target), "{}", message);
+    }
+    set_global_locale(Locale::default());
+}
+
+#[test]
+fn depth_and_split_count_outside_limits_are_rejected() {
+    let build = |depth, count, limits| {
+        FindRouterParams::builder()
+            .from("0x2::sui::SUI")
+            .target(
+                "0x5d4b302506645c37ff133b98c4b50a5ae14841659738d6d733d59d0d217a93bf::coin::COIN",
+            )
+            .amount(1_000u64)
+            .depth(depth)
+            .split(SplitAlgorithm::Auto { count })
+            .limits(limits)
+            .build()
+    };
+    // デフォルトでは下限のみを確認し、上限はサーバーの判断に任せる
+    let defaults = ParamLimits::default();
+    assert!(build(10, 50, defaults).is_ok());
+    for (depth, count) in [(0, 1), (1, 0)] {
+        assert_eq!(
+            build(depth, count, defaults).unwrap_err().id(),
+            "input_error",
+            "depth: {}, count: {}",
+            depth,
+            count
+        );
+    }
+
+    // 上限を設定した場合のみ送信前に拒否する
+    let limits = ParamLimits {
+        max_depth: Some(3),
+        max_split_count: Some(20),
+    };
+    assert!(build(3, 20, limits).is_ok());
+    assert!(build(4, 20, limits).is_err());
+    assert!(build(3, 21, limits).is_err());
+}
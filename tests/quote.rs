@@ -0,0 +1,66 @@
+//! 見積もりヘルパーの結合テスト
+
+mod common;
+
+use cetus_aggregator_rust::mock_server::{MockServer, ServerResponse};
+use cetus_aggregator_rust::{AggregatorClientTrait, AggregatorResponse};
+use primitive_types::U256;
+
+#[test]
+fn slippage_bounds_follow_the_quote_direction() {
+    let mut data = common::swap(1_000, 2_000);
+    assert_eq!(data.min_amount_out(50).unwrap(), U256::from(1_990u64));
+    assert_eq!(data.max_amount_in(50).unwrap_err().id(), "input_error");
+
+    data.by_amount_in = Some(false);
+    assert_eq!(data.max_amount_in(50).unwrap(), U256::from(1_005u64));
+    assert_eq!(data.min_amount_out(50).unwrap_err().id(), "input_error");
+}
+
+#[tokio::test]
+async fn direction_missing_from_response_is_taken_from_the_request() {
+    let response = AggregatorResponse {
+        code: 200,
+        msg: "Success".to_string(),
+        data: Some(common::swap(1_000, 2_000)),
+    };
+    let mut body = serde_json::to_value(response).unwrap();
+    body["data"].as_object_mut().unwrap().remove("by_amount_in");
+
+    let server = MockServer::start().await.unwrap();
+    server.respond(ServerResponse::json(200, &body));
+    let client = server.client();
+
+    let data = client
+        .find_routers(common::params(1_000))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(data.min_amount_out(50).unwrap(), U256::from(1_990u64));
+
+    let mut params = common::params(2_000);
+    params.by_amount_in = false;
+    let data = client.find_routers(params).await.unwrap().unwrap();
+    assert_eq!(data.max_amount_in(50).unwrap(), U256::from(1_005u64));
+}
+
+#[test]
+fn unknown_direction_is_rejected() {
+    let mut data = common::swap(1_000, 2_000);
+    data.by_amount_in = None;
+
+    assert_eq!(
+        data.min_amount_out(50).unwrap_err().id(),
+        "invalid_response"
+    );
+    assert_eq!(data.max_amount_in(50).unwrap_err().id(), "invalid_response");
+}
+
+#[test]
+fn overflow_from_response_amounts_is_an_invalid_response() {
+    let mut data = common::swap(1_000, 2_000);
+    data.by_amount_in = Some(false);
+    data.amount_in = U256::MAX;
+
+    assert_eq!(data.max_amount_in(50).unwrap_err().id(), "invalid_response");
+}
@@ -0,0 +1,50 @@
+//! 有理数の解析の結合テスト
+
+mod common;
+
+use cetus_aggregator_rust::{AggregatorResponse, Ratio};
+use primitive_types::U256;
+
+#[test]
+fn zero_parses_regardless_of_exponent_and_trailing_zeros() {
+    for input in ["0e-100", "0e100", &format!("0.{}", "0".repeat(80))] {
+        assert_eq!(input.parse::<Ratio>().unwrap(), Ratio::zero(), "{}", input);
+    }
+}
+
+#[test]
+fn trailing_fraction_zeros_do_not_overflow() {
+    let ratio: Ratio = format!("1.5{}", "0".repeat(80)).parse().unwrap();
+    assert_eq!(ratio, Ratio::new(U256::from(3u8), U256::from(2u8)).unwrap());
+
+    // 0以外の値が`U256`に収まらない場合はエラー
+    assert!("1e100".parse::<Ratio>().is_err());
+}
+
+#[test]
+fn digits_below_the_denominator_limit_are_rounded() {
+    let smallest = Ratio::new(U256::one(), U256::exp10(77)).unwrap();
+    assert_eq!("1e-100".parse::<Ratio>().unwrap(), Ratio::zero());
+    assert_eq!("4.9e-78".parse::<Ratio>().unwrap(), Ratio::zero());
+    assert_eq!("5e-78".parse::<Ratio>().unwrap(), smallest);
+
+    let ratio: Ratio = format!("0.25{}1", "0".repeat(80)).parse().unwrap();
+    assert_eq!(ratio, Ratio::new(U256::one(), U256::from(4u8)).unwrap());
+}
+
+#[test]
+fn tiny_fee_rate_does_not_break_the_response() {
+    let response = AggregatorResponse {
+        code: 200,
+        msg: "Success".to_string(),
+        data: Some(common::swap(1_000, 30)),
+    };
+    let mut body = serde_json::to_value(response).unwrap();
+    body["data"]["routes"][0]["path"][0]["fee_rate"] = "1e-90".into();
+
+    let response: AggregatorResponse = serde_json::from_value(body).unwrap();
+    assert_eq!(
+        response.data.unwrap().routes[0].path[0].fee_rate,
+        Ratio::zero()
+    );
+}
@@ -0,0 +1,147 @@
+//! リトライポリシーの結合テスト
+
+mod common;
+
+use std::time::{Duration, Instant};
+
+use cetus_aggregator_rust::mock_server::{MockServer, ServerResponse};
+use cetus_aggregator_rust::{
+    AggregatorClient, AggregatorClientTrait, AggregatorError, RetryPolicy,
+};
+
+fn fast_policy(max_retries: u32) -> RetryPolicy {
+    RetryPolicy {
+        max_retries,
+        initial_backoff: Duration::from_millis(1),
+        max_backoff: Duration::from_secs(2),
+        jitter: 0.0,
+        ..Default::default()
+    }
+}
+
+fn client(server: &MockServer, policy: RetryPolicy) -> AggregatorClient {
+    AggregatorClient::builder()
+        .endpoint(server.url())
+        .retry_policy(policy)
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn backoff_grows_exponentially_up_to_max() {
+    let policy = RetryPolicy {
+        initial_backoff: Duration::from_millis(100),
+        max_backoff: Duration::from_millis(500),
+        multiplier: 2.0,
+        ..Default::default()
+    };
+    let backoffs: Vec<u128> = (0..5)
+        .map(|attempt| policy.base_backoff(attempt).as_millis())
+        .collect();
+    assert_eq!(backoffs, vec![100, 200, 400, 500, 500]);
+}
+
+#[test]
+fn jitter_only_shortens_backoff() {
+    let policy = RetryPolicy {
+        initial_backoff: Duration::from_millis(100),
+        jitter: 0.5,
+        ..Default::default()
+    };
+    for _ in 0..100 {
+        let backoff = policy.backoff(0);
+        assert!(backoff >= Duration::from_millis(50) && backoff <= Duration::from_millis(100));
+    }
+}
+
+#[test]
+fn non_finite_jitter_does_not_panic() {
+    let policy = |jitter| RetryPolicy {
+        initial_backoff: Duration::from_millis(100),
+        jitter,
+        ..Default::default()
+    };
+    assert_eq!(policy(f64::NAN).backoff(0), Duration::from_millis(100));
+    assert!(policy(f64::INFINITY).backoff(0) <= Duration::from_millis(100));
+    assert_eq!(
+        policy(f64::NEG_INFINITY).backoff(0),
+        Duration::from_millis(100)
+    );
+}
+
+#[tokio::test]
+async fn retries_server_errors_until_success() {
+    let server = MockServer::start().await.unwrap();
+    server
+        .enqueue(ServerResponse::status(503, "unavailable"))
+        .enqueue(ServerResponse::status(502, "bad gateway"))
+        .respond(ServerResponse::success(common::swap(10, 20)));
+
+    let data = client(&server, fast_policy(3))
+        .find_routers(common::params(10))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(data.amount_out, 20u64.into());
+    assert_eq!(server.request_count(), 3);
+}
+
+#[tokio::test]
+async fn gives_up_after_max_retries() {
+    let server = MockServer::start().await.unwrap();
+    server.respond(ServerResponse::status(503, "unavailable"));
+
+    let error = client(&server, fast_policy(2))
+        .find_routers(common::params(10))
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        AggregatorError::HttpError { status: 503, .. }
+    ));
+    assert_eq!(server.request_count(), 3);
+}
+
+#[tokio::test]
+async fn does_not_retry_client_errors() {
+    let server = MockServer::start().await.unwrap();
+    server.respond(ServerResponse::status(400, "bad request"));
+
+    let error = client(&server, fast_policy(3))
+        .find_routers(common::params(10))
+        .await
+        .unwrap_err();
+    assert!(!error.is_retryable());
+    assert_eq!(server.request_count(), 1);
+}
+
+#[tokio::test]
+async fn waits_for_retry_after() {
+    let server = MockServer::start().await.unwrap();
+    server
+        .enqueue(ServerResponse::status(429, "slow down").with_header("Retry-After", "1"))
+        .respond(ServerResponse::success(common::swap(10, 20)));
+
+    let started = Instant::now();
+    client(&server, fast_policy(1))
+        .find_routers(common::params(10))
+        .await
+        .unwrap();
+    assert!(started.elapsed() >= Duration::from_secs(1));
+    assert_eq!(server.request_count(), 2);
+}
+
+#[tokio::test]
+async fn gives_up_when_retry_after_exceeds_max_backoff() {
+    let server = MockServer::start().await.unwrap();
+    server.respond(ServerResponse::status(503, "maintenance").with_header("Retry-After", "86400"));
+
+    let started = Instant::now();
+    let error = client(&server, fast_policy(3))
+        .find_routers(common::params(10))
+        .await
+        .unwrap_err();
+    assert!(started.elapsed() < Duration::from_secs(1));
+    assert_eq!(error.retry_after(), Some(Duration::from_secs(86400)));
+    assert_eq!(server.request_count(), 1);
+}
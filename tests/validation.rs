@@ -0,0 +1,240 @@
+//! ルート検証の結合テスト
+
+mod common;
+
+use cetus_aggregator_rust::fixture::{PathBuilder, RouteBuilder, RouterDataBuilder};
+use cetus_aggregator_rust::mock_server::{MockServer, ServerResponse};
+use cetus_aggregator_rust::{
+    AggregatorClientTrait, AggregatorResponse, CoinType, ExtendedDetailsError, Locale, Path,
+    Provider, ProviderDetails, RouteViolation,
+};
+use primitive_types::U256;
+use serde_json::json;
+
+fn cetus() -> CoinType {
+    "0x6864a6f921804860930db6ddbe2e16acdf8504495ea7481637a1c8b9a8fe54b::cetus::CETUS"
+        .parse()
+        .unwrap()
+}
+
+fn hop(from: CoinType, target: CoinType, amount_in: u64, amount_out: u64) -> Path {
+    PathBuilder::new(Provider::Cetus, from, target, amount_in, amount_out).build()
+}
+
+#[test]
+fn consistent_routes_pass() {
+    let data = RouterDataBuilder::multi_hop(
+        Provider::Cetus,
+        &[common::sui(), cetus(), common::usdc()],
+        &[U256::from(1_000u64), U256::from(500u64), U256::from(30u64)],
+    )
+    .build();
+    assert_eq!(data.validate(&common::params(1_000)), Ok(()));
+}
+
+#[test]
+fn detects_discontinuous_coins_and_amounts() {
+    let route = RouteBuilder::new()
+        .hop(hop(common::sui(), cetus(), 1_000, 500))
+        .hop(hop(common::usdc(), common::usdc(), 499, 30))
+        .build();
+    let data = RouterDataBuilder::new().route(route).build();
+
+    let violations = data.validate(&common::params(1_000)).unwrap_err();
+    assert!(violations.contains(&RouteViolation::DiscontinuousPath {
+        route: 0,
+        hop: 1,
+        expected: cetus(),
+        actual: common::usdc(),
+    }));
+    assert!(violations.contains(&RouteViolation::HopAmountMismatch {
+        route: 0,
+        hop: 1,
+        expected: U256::from(500u64),
+        actual: U256::from(499u64),
+    }));
+}
+
+#[test]
+fn detects_coin_and_amount_mismatches_with_request() {
+    let data = RouterDataBuilder::swap(cetus(), common::usdc(), 900u64, 30u64).build();
+
+    let violations = data.validate(&common::params(1_000)).unwrap_err();
+    assert!(violations.contains(&RouteViolation::FromMismatch {
+        route: 0,
+        expected: common::sui(),
+        actual: cetus(),
+    }));
+    assert!(
+        violations.contains(&RouteViolation::RequestedAmountMismatch {
+            expected: U256::from(1_000u64),
+            actual: U256::from(900u64),
+        })
+    );
+}
+
+#[test]
+fn detects_totals_that_do_not_match_routes() {
+    let mut data = common::swap(1_000, 30);
+    data.amount_out = U256::from(31u64);
+
+    let violations = data.validate(&common::params(1_000)).unwrap_err();
+    assert_eq!(
+        violations,
+        vec![RouteViolation::TotalAmountOutMismatch {
+            expected: U256::from(31u64),
+            actual: U256::from(30u64),
+        }]
+    );
+}
+
+#[test]
+fn detects_depth_and_direction_violations() {
+    let data = RouterDataBuilder::multi_hop(
+        Provider::Cetus,
+        &[common::sui(), cetus(), common::usdc()],
+        &[U256::from(1_000u64), U256::from(500u64), U256::from(30u64)],
+    )
+    .by_amount_in(false)
+    .build();
+    let mut params = common::params(1_000);
+    params.depth = Some(1);
+
+    let violations = data.validate(&params).unwrap_err();
+    assert!(violations.contains(&RouteViolation::DepthExceeded {
+        route: 0,
+        hops: 2,
+        depth: 1,
+    }));
+    assert!(violations.contains(&RouteViolation::DirectionMismatch {
+        expected: true,
+        actual: false,
+    }));
+}
+
+#[tokio::test]
+async fn missing_direction_from_server_is_filled_from_request() {
+    let server = MockServer::start().await.unwrap();
+    for by_amount_in in [true, false] {
+        let response = AggregatorResponse {
+            code: 200,
+            msg: "Success".to_string(),
+            data: Some(common::swap(1_000, 30)),
+        };
+        let mut body = serde_json::to_value(response).unwrap();
+        body["data"].as_object_mut().unwrap().remove("by_amount_in");
+        server.respond(ServerResponse::json(200, &body));
+
+        let mut params = common::params(if by_amount_in { 1_000 } else { 30 });
+        params.by_amount_in = by_amount_in;
+        let data = server
+            .client()
+            .find_routers(params.clone())
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(data.by_amount_in, Some(by_amount_in));
+        assert_eq!(data.validate(&params), Ok(()));
+    }
+}
+
+#[test]
+fn missing_direction_is_reported() {
+    let mut data = common::swap(1_000, 30);
+    data.by_amount_in = None;
+    assert_eq!(
+        data.validate(&common::params(1_000)),
+        Err(vec![RouteViolation::MissingDirection { expected: true }])
+    );
+}
+
+#[tokio::test]
+async fn incomplete_provider_details_are_kept_and_reported() {
+    let data = RouterDataBuilder::new()
+        .route(
+            RouteBuilder::new()
+                .hop(
+                    PathBuilder::new(Provider::Steamm, common::sui(), cetus(), 1_000u64, 500u64)
+                        .build(),
+                )
+                .hop(
+                    PathBuilder::new(Provider::Turbos, cetus(), common::usdc(), 500u64, 30u64)
+                        .build(),
+                )
+                .build(),
+        )
+        .build();
+    let response = AggregatorResponse {
+        code: 200,
+        msg: "Success".to_string(),
+        data: Some(data),
+    };
+    let mut body = serde_json::to_value(response).unwrap();
+    let paths = body["data"]["routes"][0]["path"].as_array_mut().unwrap();
+    paths[0]["extended_details"] = json!({
+        "steamm_bank_a": "0xa",
+        "after_sqrt_price": "42",
+    });
+
+    let server = MockServer::start().await.unwrap();
+    server.respond(ServerResponse::json(200, &body));
+    let data = server
+        .client()
+        .find_routers(common::params(1_000))
+        .await
+        .unwrap()
+        .unwrap();
+
+    // 受信したフィールドは保持される
+    let details = data.routes[0].path[0].extended_details.as_ref().unwrap();
+    assert!(matches!(
+        details.provider_details,
+        ProviderDetails::Invalid(ExtendedDetailsError::MissingFields { .. })
+    ));
+    assert_eq!(details.after_sqrt_price, Some(42));
+    assert_eq!(details.extra["steamm_bank_a"], json!("0xa"));
+
+    let violations = data.validate(&common::params(1_000)).unwrap_err();
+    assert_eq!(violations.len(), 2);
+    assert!(matches!(
+        &violations[0],
+        RouteViolation::InvalidExtendedDetails {
+            route: 0,
+            hop: 0,
+            error: ExtendedDetailsError::MissingFields { provider: Provider::Steamm, fields },
+        } if fields.len() == 6 && !fields.contains(&"steamm_bank_a")
+    ));
+    assert_eq!(
+        violations[1],
+        RouteViolation::InvalidExtendedDetails {
+            route: 0,
+            hop: 1,
+            error: ExtendedDetailsError::MissingFields {
+                provider: Provider::Turbos,
+                fields: vec!["turbos_fee_type"],
+            },
+        }
+    );
+}
+
+#[test]
+fn violations_are_localized() {
+    let violation = RouteViolation::InvalidExtendedDetails {
+        route: 0,
+        hop: 1,
+        error: ExtendedDetailsError::MissingFields {
+            provider: Provider::Turbos,
+            fields: vec!["turbos_fee_type"],
+        },
+    };
+
+    assert_eq!(
+        violation.to_localized_string(Locale::English),
+        "extended details of hop 1 of route 0 are invalid: extended details of TURBOS are missing required fields: turbos_fee_type"
+    );
+    assert_eq!(
+        violation.to_localized_string(Locale::Japanese),
+        "ルート0のホップ1の拡張詳細情報が不正です: TURBOSの拡張詳細情報に必須フィールドがありません: turbos_fee_type"
+    );
+}